@@ -84,6 +84,7 @@ impl epi::App for RayTracer {
         _frame: &epi::Frame,
         _storage: Option<&dyn epi::Storage>,
     ) {
+        self.world.build_bvh();
         let world = self.world.clone();
         let up_to_date = self.preview_up_to_date.clone();
         let img_arc = self.preview_image.clone();
@@ -127,14 +128,18 @@ impl epi::App for RayTracer {
             if let Some(arc_curr_obj) = self.get_active_object() {
                 let curr_obj = arc_curr_obj.write().unwrap();
                 match *curr_obj {
-                    ObjectHolder::Object(object) => {
+                    ObjectHolder::Object(ref object) => {
                         match object.shape {
                             Shape::Cone {min: _, max: _, closed: _} => &self.shape_specific_interface(ui, curr_obj),
                             Shape::Cylinder {min: _, max: _, closed: _} => &self.shape_specific_interface(ui, curr_obj),
+                            Shape::Torus {..} => &self.shape_specific_interface(ui, curr_obj),
+                            Shape::Disk {..} => &self.shape_specific_interface(ui, curr_obj),
+                            Shape::Quad {..} => &self.shape_specific_interface(ui, curr_obj),
                             _ => &(),
                         };
                     },
-                    ObjectHolder::Group(_) => ()
+                    ObjectHolder::Group(_) | ObjectHolder::Csg(_) | ObjectHolder::Heightfield(_) |
+                        ObjectHolder::Instance(_) => ()
                 };
             };
 
@@ -194,8 +199,10 @@ impl RayTracer {
         }
     }
 
+    // Every edit ends here, so the hierarchy is rebuilt once per change rather than once per frame rendered.
     fn prep_update(&mut self) {
         self.preview_up_to_date.store(false, Ordering::Relaxed);
+        self.world.build_bvh();
         let wd = self.world.clone();
         self.world_sender.send(wd);
     }
@@ -207,6 +214,11 @@ impl RayTracer {
             Shape::Cylinder {min, max, closed} => cylinders::new(min, max, closed),
             Shape::Plane => planes::new(),
             Shape::Sphere => spheres::new(),
+            Shape::Torus {major, minor} => tori::new(major, minor),
+            Shape::Disk {radius, inner} => disks::new_annulus(radius, inner),
+            Shape::Quad {width, depth} => quads::new(width, depth),
+            Shape::Triangle {..} | Shape::SmoothTriangle {..} | Shape::Sdf {..} | Shape::Metaballs {..} =>
+                Object::new(shape),
         };
         self.world.add_object(new);
         self.prep_update();
@@ -217,6 +229,20 @@ impl RayTracer {
         self.world.add_group(grp);
     }
 
+    // Replaces the active object and the object at `other` with a CSG node combining them.
+    fn add_new_csg(&mut self, operation: CsgOperation, other: usize) {
+        if let Some(active) = self.active_object {
+            let left = self.world.objects()[active].read().unwrap().clone();
+            let right = self.world.objects()[other].read().unwrap().clone();
+            let objects = self.world.mut_objects();
+            objects.remove(active.max(other));
+            objects.remove(active.min(other));
+            self.world.add_csg(Csg::new(operation, left, right));
+            self.active_object = Some(self.world.objects().len() - 1);
+            self.prep_update();
+        }
+    }
+
     fn material_attribute_slider(&mut self, index: u8, ui: &mut egui::Ui, enabled: bool, object: &mut Object) {
         if enabled {
             let (mut orig, name, rng) = self.get_scalar_attribute_from_index(index, object);
@@ -374,6 +400,55 @@ impl RayTracer {
                             });
                         });
                     }
+                    Shape::Torus {ref mut major, ref mut minor} => {
+                        ui.add(egui::Separator::default());
+                        ui.with_layout(egui::Layout::top_down_justified(Align::Center), |ui| {
+                            ui.label("Shape-specific attributes");
+                            ui.horizontal(|ui| {
+                                ui.label("Major radius");
+                                if ui.add(egui::DragValue::new(major).speed(0.1).clamp_range(0.0..=f64::MAX)).changed() {
+                                    self.prep_update();
+                                };
+                                ui.label("Minor radius");
+                                if ui.add(egui::DragValue::new(minor).speed(0.05).clamp_range(0.0..=f64::MAX)).changed() {
+                                    self.prep_update();
+                                };
+                            });
+                        });
+                    }
+                    Shape::Disk {ref mut radius, ref mut inner} => {
+                        ui.add(egui::Separator::default());
+                        ui.with_layout(egui::Layout::top_down_justified(Align::Center), |ui| {
+                            ui.label("Shape-specific attributes");
+                            ui.horizontal(|ui| {
+                                ui.label("Radius");
+                                // Each radius is kept on its own side of the other, so the hole never outgrows the disk.
+                                if ui.add(egui::DragValue::new(radius).speed(0.1).clamp_range(*inner..=f64::MAX)).changed() {
+                                    self.prep_update();
+                                };
+                                ui.label("Inner radius");
+                                if ui.add(egui::DragValue::new(inner).speed(0.1).clamp_range(0.0..=*radius)).changed() {
+                                    self.prep_update();
+                                };
+                            });
+                        });
+                    }
+                    Shape::Quad {ref mut width, ref mut depth} => {
+                        ui.add(egui::Separator::default());
+                        ui.with_layout(egui::Layout::top_down_justified(Align::Center), |ui| {
+                            ui.label("Shape-specific attributes");
+                            ui.horizontal(|ui| {
+                                ui.label("Width");
+                                if ui.add(egui::DragValue::new(width).speed(0.1).clamp_range(0.0..=f64::MAX)).changed() {
+                                    self.prep_update();
+                                };
+                                ui.label("Depth");
+                                if ui.add(egui::DragValue::new(depth).speed(0.1).clamp_range(0.0..=f64::MAX)).changed() {
+                                    self.prep_update();
+                                };
+                            });
+                        });
+                    }
                     _ => ()
                 }
             }
@@ -405,17 +480,48 @@ impl RayTracer {
                         if ui.button("Cylinder").clicked() {
                             self.add_new_shape(Shape::Cylinder {min: 0.0, max: 1.0, closed: true});
                         }
+                        if ui.button("Disk").clicked() {
+                            self.add_new_shape(Shape::Disk {radius: 1.0, inner: 0.0});
+                        }
                         if ui.button("Plane").clicked() {
                             self.add_new_shape(Shape::Plane);
                         }
+                        if ui.button("Quad").clicked() {
+                            self.add_new_shape(Shape::Quad {width: 1.0, depth: 1.0});
+                        }
                         if ui.button("Sphere").clicked() {
                             self.add_new_shape(Shape::Sphere);
                         }
+                        if ui.button("Torus").clicked() {
+                            self.add_new_shape(Shape::Torus {major: 1.0, minor: 0.25});
+                        }
                     });
                     if ui.button("New Group").clicked() {
                         self.add_new_group();
                     }
 
+                    ui.group(|ui| {
+                        ui.set_enabled(self.active_object.is_some());
+                        ui.menu_button("New CSG with active object...", |ui| {
+                            for operation in [CsgOperation::Union, CsgOperation::Intersection, CsgOperation::Difference] {
+                                ui.menu_button(format!("{}", operation), |ui| {
+                                    let mut chosen = None;
+                                    for (i, item) in self.world.objects().iter().enumerate() {
+                                        if Some(i) == self.active_object {
+                                            continue;
+                                        }
+                                        if ui.button(format!("{}. {}", i+1, item.read().unwrap())).clicked() {
+                                            chosen = Some(i);
+                                        }
+                                    }
+                                    if let Some(other) = chosen {
+                                        self.add_new_csg(operation, other);
+                                    }
+                                });
+                            }
+                        });
+                    });
+
                     ui.group(|ui| {
                         ui.set_enabled(self.active_object.is_some() && self.get_active_object().unwrap().read().unwrap().is_group());
                         ui.menu_button("Add objects to group", |ui| {
@@ -456,8 +562,9 @@ impl RayTracer {
                                 active_group.remove_from_group(remove);
                             };
                             std::mem::drop(active_group);
-                            if removed_item.is_some() {
-                                world_objects.push(Arc::new(RwLock::new(removed_item.unwrap())));
+                            if let Some(mut item) = removed_item {
+                                item.set_parent_transform(Matrix4::identity());
+                                world_objects.push(Arc::new(RwLock::new(item)));
                             }
                         });
                     });
@@ -492,6 +599,54 @@ impl RayTracer {
                     });
                 };
 
+                ui.separator();
+
+                let arc_preview_camera = self.preview_camera.clone();
+                let mut samples = self.camera.get_samples();
+                if ui.add(egui::DragValue::new(&mut samples).speed(1).clamp_range(1..=256)).changed() {
+                    self.camera.set_samples(samples);
+                    let mut prev = arc_preview_camera.write().unwrap();
+                    *prev = Camera::new_preview(&self.camera);
+                    self.prep_update();
+                }
+                ui.label("Samples per pixel");
+
+                let mut sampling = self.camera.get_sampling();
+                egui::ComboBox::from_label("Sampling")
+                    .selected_text(format!("{}", sampling))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut sampling, SamplingStrategy::Grid, "Grid");
+                        ui.selectable_value(&mut sampling, SamplingStrategy::Jittered, "Jittered");
+                        ui.selectable_value(&mut sampling, SamplingStrategy::Random, "Random");
+                        ui.selectable_value(&mut sampling, SamplingStrategy::Adaptive, "Adaptive");
+                    });
+                if sampling != self.camera.get_sampling() {
+                    self.camera.set_sampling(sampling);
+                    let mut prev = arc_preview_camera.write().unwrap();
+                    *prev = Camera::new_preview(&self.camera);
+                    self.prep_update();
+                }
+
+                if sampling == SamplingStrategy::Adaptive {
+                    let mut threshold = self.camera.get_adaptive_threshold();
+                    if ui.add(egui::DragValue::new(&mut threshold).speed(0.01).clamp_range(0.0..=1.0)).changed() {
+                        self.camera.set_adaptive_threshold(threshold);
+                        let mut prev = arc_preview_camera.write().unwrap();
+                        *prev = Camera::new_preview(&self.camera);
+                        self.prep_update();
+                    }
+                    ui.label("Threshold");
+
+                    let mut depth = self.camera.get_adaptive_depth();
+                    if ui.add(egui::DragValue::new(&mut depth).speed(1).clamp_range(1..=4)).changed() {
+                        self.camera.set_adaptive_depth(depth);
+                        let mut prev = arc_preview_camera.write().unwrap();
+                        *prev = Camera::new_preview(&self.camera);
+                        self.prep_update();
+                    }
+                    ui.label("Depth");
+                }
+
                 if self.rendering.load(Ordering::SeqCst) {
                     let progress = self.rendering_progress.load(Ordering::SeqCst) as f32 /
                         (self.camera.get_vsize() * self.camera.get_vsize()) as f32;
@@ -540,6 +695,55 @@ impl RayTracer {
                 ui.label("FOV");
             });
 
+            ui.horizontal(|ui| {
+                let mut aperture = self.camera.get_aperture();
+                if ui.add(egui::DragValue::new(&mut aperture).speed(0.01).clamp_range(0.0..=f64::MAX)).changed() {
+                    self.camera.set_aperture(aperture);
+                    let mut prev = arc_preview_camera.write().unwrap();
+                    *prev = Camera::new_preview(&self.camera);
+                    self.prep_update();
+                }
+                ui.label("Aperture");
+
+                let mut focal_distance = self.camera.get_focal_distance();
+                if ui.add(egui::DragValue::new(&mut focal_distance).speed(0.1).clamp_range(0.0..=f64::MAX)).changed() {
+                    self.camera.set_focal_distance(focal_distance);
+                    let mut prev = arc_preview_camera.write().unwrap();
+                    *prev = Camera::new_preview(&self.camera);
+                    self.prep_update();
+                }
+                ui.label("Focal distance");
+            });
+
+            ui.horizontal(|ui| {
+                let mut projection = self.camera.get_projection();
+                egui::ComboBox::from_label("Projection")
+                    .selected_text(format!("{}", projection))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut projection, Projection::Perspective, "Perspective");
+                        ui.selectable_value(&mut projection, Projection::Orthographic, "Orthographic");
+                        ui.selectable_value(&mut projection, Projection::Equirectangular, "Equirectangular");
+                        ui.selectable_value(&mut projection, Projection::Fisheye, "Fisheye");
+                    });
+                if projection != self.camera.get_projection() {
+                    self.camera.set_projection(projection);
+                    let mut prev = arc_preview_camera.write().unwrap();
+                    *prev = Camera::new_preview(&self.camera);
+                    self.prep_update();
+                }
+
+                if projection == Projection::Orthographic {
+                    let mut view_width = self.camera.get_view_width();
+                    if ui.add(egui::DragValue::new(&mut view_width).speed(0.1).clamp_range(0.0..=f64::MAX)).changed() {
+                        self.camera.set_view_width(view_width);
+                        let mut prev = arc_preview_camera.write().unwrap();
+                        *prev = Camera::new_preview(&self.camera);
+                        self.prep_update();
+                    }
+                    ui.label("View width");
+                }
+            });
+
             ui.add_space(4.0);
 
             ui.horizontal(|ui| {
@@ -669,7 +873,7 @@ impl RayTracer {
                         };
 
                         ui.horizontal(|ui| {
-                            let ptrn = object.get_pattern();
+                            let ptrn = object.get_pattern().clone();
                             if ptrn.get_pattern_type() != PatternType::Solid {
                                 let clrs = ptrn.colors();
                                 let mut color1 = [
@@ -825,6 +1029,8 @@ impl RayTracer {
                             string.push_str(&*format!("\n  - {}", obj))
                         }
                         string
+                    } else if let Ok(csg) = object.get_csg() {
+                        format!("{}. {}\n  - {}\n  - {}", i+1, object, csg.left(), csg.right())
                     } else {
                         format!("{}. {}", i+1, object)
                     }
@@ -0,0 +1,4 @@
+pub mod obj;
+pub mod stl;
+pub mod ply;
+pub mod patches;
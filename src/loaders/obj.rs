@@ -0,0 +1,300 @@
+use crate::prelude::*;
+
+// A triangle waiting for the mesh: its group, vertex indices and, for smooth faces, normal indices.
+type Face = (Option<usize>, [usize; 3], Option<[usize; 3]>);
+
+// Result of reading a Wavefront OBJ file. Vertex and normal lists are 1-indexed like the file itself.
+#[derive(Clone, Debug)]
+pub struct ObjParser {
+    vertices: Vec<Tuple>,
+    normals: Vec<Tuple>,
+    faces: Vec<Face>,
+    default_group: Group,
+    named_groups: Vec<(String, Group)>,
+    current_group: Option<usize>,
+    ignored_lines: usize,
+}
+
+impl ObjParser {
+    fn new() -> Self {
+        Self {
+            vertices: vec![],
+            normals: vec![],
+            faces: vec![],
+            default_group: Group::new_empty(),
+            named_groups: vec![],
+            current_group: None,
+            ignored_lines: 0,
+        }
+    }
+
+    pub fn get_vertex(&self, index: usize) -> Option<Tuple> {
+        index.checked_sub(1).and_then(|i| self.vertices.get(i)).copied()
+    }
+
+    pub fn get_normal(&self, index: usize) -> Option<Tuple> {
+        index.checked_sub(1).and_then(|i| self.normals.get(i)).copied()
+    }
+
+    pub fn get_default_group(&self) -> &Group {
+        &self.default_group
+    }
+
+    pub fn get_named_group(&self, name: &str) -> Option<&Group> {
+        self.named_groups.iter().find(|(n, _)| n == name).map(|(_, g)| g)
+    }
+
+    // Number of lines that were not understood: unknown statements and malformed or out-of-range data.
+    pub fn get_ignored_lines(&self) -> usize {
+        self.ignored_lines
+    }
+
+    // Everything in the file as a single group, with each named group nested as a subgroup.
+    pub fn to_group(&self) -> Group {
+        let mut group = self.default_group.clone();
+        for (_, named) in &self.named_groups {
+            group.add_group(named.clone());
+        }
+        group
+    }
+
+    fn parse_line(&mut self, line: &str) -> bool {
+        let mut tokens = line.split_whitespace();
+        let keyword = match tokens.next() {
+            Some(keyword) => keyword,
+            None => return true,
+        };
+        let args: Vec<&str> = tokens.collect();
+        match keyword {
+            "v" => match parse_coordinates(&args) {
+                Some((x, y, z)) => self.vertices.push(point(x, y, z)),
+                None => return false,
+            },
+            "vn" => match parse_coordinates(&args) {
+                Some((x, y, z)) => self.normals.push(vector(x, y, z)),
+                None => return false,
+            },
+            "f" => return self.parse_face(&args),
+            "g" | "o" => self.start_group(args.join(" ")),
+            _ if keyword.starts_with('#') => (),
+            _ => return false,
+        }
+        true
+    }
+
+    fn start_group(&mut self, name: String) {
+        match self.named_groups.iter().position(|(n, _)| *n == name) {
+            Some(index) => self.current_group = Some(index),
+            None => {
+                self.named_groups.push((name, Group::new_empty()));
+                self.current_group = Some(self.named_groups.len() - 1);
+            }
+        }
+    }
+
+    // Polygons are split into a fan of triangles around their first vertex.
+    fn parse_face(&mut self, args: &[&str]) -> bool {
+        if args.len() < 3 {
+            return false;
+        }
+        let mut corners = vec![];
+        for arg in args {
+            match self.parse_corner(arg) {
+                Some(corner) => corners.push(corner),
+                None => return false,
+            }
+        }
+
+        let smooth = corners.iter().all(|(_, normal)| normal.is_some());
+        for i in 1..corners.len() - 1 {
+            let (p1, n1) = corners[0];
+            let (p2, n2) = corners[i];
+            let (p3, n3) = corners[i + 1];
+            let normals = if smooth { Some([n1.unwrap(), n2.unwrap(), n3.unwrap()]) } else { None };
+            self.faces.push((self.current_group, [p1, p2, p3], normals));
+        }
+        true
+    }
+
+    // A face corner is "v", "v/vt", "v//vn" or "v/vt/vn"; texture coordinates are skipped.
+    // Returns 0-based indices into the vertex and normal lists.
+    fn parse_corner(&self, arg: &str) -> Option<(usize, Option<usize>)> {
+        let mut parts = arg.split('/');
+        let vertex = resolve_index(parts.next()?, self.vertices.len())
+            .filter(|&i| self.get_vertex(i).is_some())?;
+        let _texture = parts.next();
+        let normal = match parts.next() {
+            Some(index) if !index.is_empty() => {
+                Some(resolve_index(index, self.normals.len()).filter(|&i| self.get_normal(i).is_some())? - 1)
+            },
+            _ => None,
+        };
+        Some((vertex - 1, normal))
+    }
+
+    // Faces can refer to normals defined after them, so the triangles are only made once the whole
+    // file has been read. They all share one mesh, with a facet normal appended for each flat face.
+    fn build_triangles(&mut self) {
+        let mut mesh = Mesh::new();
+        for &vertex in &self.vertices {
+            mesh.add_point(vertex);
+        }
+        for &normal in &self.normals {
+            mesh.add_normal(normal);
+        }
+        for (_, points, normals) in &self.faces {
+            if normals.is_none() {
+                let [p1, p2, p3] = points.map(|i| self.vertices[i]);
+                mesh.add_normal((p3 - p1).xprod(p2 - p1).normalize());
+            }
+        }
+
+        let mesh = Arc::new(mesh);
+        let mut facet = self.normals.len();
+        for (group, points, normals) in std::mem::take(&mut self.faces) {
+            let triangle = match normals {
+                Some(normals) => smooth_triangles::from_mesh(&mesh, points, normals),
+                None => {
+                    facet += 1;
+                    triangles::from_mesh(&mesh, points, facet - 1)
+                },
+            };
+            match group {
+                Some(index) => self.named_groups[index].1.add_object(triangle),
+                None => self.default_group.add_object(triangle),
+            }
+        }
+    }
+}
+
+fn parse_coordinates(args: &[&str]) -> Option<(f64, f64, f64)> {
+    if args.len() < 3 {
+        return None;
+    }
+    let x = args[0].parse().ok()?;
+    let y = args[1].parse().ok()?;
+    let z = args[2].parse().ok()?;
+    Some((x, y, z))
+}
+
+// Negative indices count back from the most recently defined element.
+fn resolve_index(index: &str, len: usize) -> Option<usize> {
+    let index: i64 = index.parse().ok()?;
+    if index < 0 {
+        let resolved = len as i64 + index + 1;
+        if resolved > 0 {
+            Some(resolved as usize)
+        } else {
+            None
+        }
+    } else {
+        Some(index as usize)
+    }
+}
+
+pub fn parse_obj(source: &str) -> ObjParser {
+    let mut parser = ObjParser::new();
+    for line in source.lines() {
+        if !parser.parse_line(line) {
+            parser.ignored_lines += 1;
+        }
+    }
+    parser.build_triangles();
+    parser
+}
+
+pub fn parse_obj_file(path: &str) -> std::io::Result<ObjParser> {
+    Ok(parse_obj(&std::fs::read_to_string(path)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn triangle_points(holder: &ObjectHolder) -> (Tuple, Tuple, Tuple) {
+        let [p1, p2, p3] = triangles::corners(holder.get_object().unwrap());
+        (p1, p2, p3)
+    }
+
+    #[test]
+    fn test_ignores_unrecognized_lines() {
+        let gibberish = "There was a young lady named Bright\n\
+                         who traveled much faster than light.\n\
+                         She set out one day\n\
+                         in a relative way,\n\
+                         and came back the previous night.";
+        let parser = parse_obj(gibberish);
+        assert_eq!(parser.get_ignored_lines(), 5);
+        assert!(parser.get_default_group().elements().is_empty());
+    }
+
+    #[test]
+    fn test_vertex_records() {
+        let parser = parse_obj("v -1 1 0\nv -1.0000 0.5000 0.0000\nv 1 0 0\nv 1 1 0\n");
+        assert_eq!(parser.get_ignored_lines(), 0);
+        assert_eq!(parser.get_vertex(1).unwrap(), point(-1.0, 1.0, 0.0));
+        assert_eq!(parser.get_vertex(2).unwrap(), point(-1.0, 0.5, 0.0));
+        assert_eq!(parser.get_vertex(3).unwrap(), point(1.0, 0.0, 0.0));
+        assert_eq!(parser.get_vertex(4).unwrap(), point(1.0, 1.0, 0.0));
+        assert!(parser.get_vertex(0).is_none());
+    }
+
+    #[test]
+    fn test_triangle_faces() {
+        let parser = parse_obj("v -1 1 0\nv -1 0 0\nv 1 0 0\nv 1 1 0\n\nf 1 2 3\nf 1 3 4\n");
+        let g = parser.get_default_group();
+        assert_eq!(g.elements().len(), 2);
+        assert_eq!(triangle_points(&g.elements()[0]),
+                   (point(-1.0, 1.0, 0.0), point(-1.0, 0.0, 0.0), point(1.0, 0.0, 0.0)));
+        assert_eq!(triangle_points(&g.elements()[1]),
+                   (point(-1.0, 1.0, 0.0), point(1.0, 0.0, 0.0), point(1.0, 1.0, 0.0)));
+    }
+
+    #[test]
+    fn test_triangulating_polygons() {
+        let parser = parse_obj("v -1 1 0\nv -1 0 0\nv 1 0 0\nv 1 1 0\nv 0 2 0\n\nf 1 2 3 4 5\n");
+        let g = parser.get_default_group();
+        assert_eq!(g.elements().len(), 3);
+        assert_eq!(triangle_points(&g.elements()[2]),
+                   (point(-1.0, 1.0, 0.0), point(1.0, 1.0, 0.0), point(0.0, 2.0, 0.0)));
+    }
+
+    #[test]
+    fn test_named_groups() {
+        let parser = parse_obj("v -1 1 0\nv -1 0 0\nv 1 0 0\nv 1 1 0\n\
+                                g FirstGroup\nf 1 2 3\no SecondGroup\nf 1 3 4\n");
+        assert!(parser.get_default_group().elements().is_empty());
+        let first = parser.get_named_group("FirstGroup").unwrap();
+        let second = parser.get_named_group("SecondGroup").unwrap();
+        assert_eq!(triangle_points(&first.elements()[0]).2, point(1.0, 0.0, 0.0));
+        assert_eq!(triangle_points(&second.elements()[0]).2, point(1.0, 1.0, 0.0));
+
+        let g = parser.to_group();
+        assert_eq!(g.elements().len(), 2);
+        assert!(g.elements().iter().all(|e| e.is_group()));
+    }
+
+    #[test]
+    fn test_vertex_normals_make_smooth_triangles() {
+        let parser = parse_obj("v 0 1 0\nv -1 0 0\nv 1 0 0\n\
+                                vn -1 0 0\nvn 1 0 0\nvn 0 1 0\n\
+                                f 1//3 2//1 3//2\nf 1/0/3 2/102/1 3/14/2\n");
+        assert_eq!(parser.get_normal(1).unwrap(), vector(-1.0, 0.0, 0.0));
+        let g = parser.get_default_group();
+        assert_eq!(g.elements().len(), 2);
+        for element in g.elements() {
+            let t = element.get_object().unwrap();
+            assert!(matches!(t.shape, Shape::SmoothTriangle {..}));
+            assert_eq!(smooth_triangles::normal_at(t, 0.0, 0.0), vector(0.0, 1.0, 0.0));
+            assert_eq!(smooth_triangles::normal_at(t, 1.0, 0.0), vector(-1.0, 0.0, 0.0));
+            assert_eq!(smooth_triangles::normal_at(t, 0.0, 1.0), vector(1.0, 0.0, 0.0));
+        }
+    }
+
+    #[test]
+    fn test_negative_and_invalid_indices() {
+        let parser = parse_obj("v 0 1 0\nv -1 0 0\nv 1 0 0\nf -3 -2 -1\nf 1 2 9\nf 1 2\n# comment\n");
+        assert_eq!(parser.get_default_group().elements().len(), 1);
+        assert_eq!(parser.get_ignored_lines(), 2);
+    }
+}
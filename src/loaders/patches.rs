@@ -0,0 +1,179 @@
+use crate::prelude::*;
+
+// Reads the classic Bézier patch format used for the Utah teapot: the number of patches, one line of
+// 16 one-based vertex indices per patch, the number of vertices, then one x, y, z line per vertex.
+// Values may be separated by commas, whitespace or both. Blank lines are skipped.
+#[derive(Debug)]
+pub enum PatchError {
+    Io(std::io::Error),
+    Parse {line: usize, message: String},
+}
+
+impl std::fmt::Display for PatchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            PatchError::Io(err) => write!(f, "{}", err),
+            PatchError::Parse {line, message} => write!(f, "line {}: {}", line, message),
+        }
+    }
+}
+
+impl std::error::Error for PatchError {}
+
+impl From<std::io::Error> for PatchError {
+    fn from(err: std::io::Error) -> Self {
+        PatchError::Io(err)
+    }
+}
+
+pub fn parse_patches_file(path: &str, resolution: usize) -> Result<Group, PatchError> {
+    parse_patches(&std::fs::read_to_string(path)?, resolution)
+}
+
+// Tessellates every patch at the given resolution into a single group of smooth triangles.
+pub fn parse_patches(text: &str, resolution: usize) -> Result<Group, PatchError> {
+    let mut group = Group::new_empty();
+    for patch in read_patches(text)? {
+        patch.tessellate_into(resolution, &mut group);
+    }
+    Ok(group)
+}
+
+pub fn read_patches(text: &str) -> Result<Vec<BezierPatch>, PatchError> {
+    let mut lines = text.lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty());
+
+    // Counts aren't used to reserve storage, so a corrupt one just runs out of lines.
+    let (count_line, patch_count) = read_count(&mut lines, "patch count")?;
+    let mut indices = vec![];
+    for _ in 0..patch_count {
+        let (line, values) = read_values::<usize>(&mut lines, 16, "patch", count_line)?;
+        indices.push((line, values));
+    }
+
+    let (count_line, vertex_count) = read_count(&mut lines, "vertex count")?;
+    let mut vertices = vec![];
+    for _ in 0..vertex_count {
+        let (_, values) = read_values::<f64>(&mut lines, 3, "vertex", count_line)?;
+        vertices.push(point(values[0], values[1], values[2]));
+    }
+
+    if let Some((line, _)) = lines.next() {
+        return Err(parse_error(line, "unexpected data after the last vertex"));
+    }
+
+    indices.into_iter().map(|(line, values)| {
+        let mut control_points = [origin(); 16];
+        for (control_point, index) in control_points.iter_mut().zip(values) {
+            *control_point = match index.checked_sub(1).and_then(|i| vertices.get(i)) {
+                Some(vertex) => *vertex,
+                None => return Err(parse_error(line, &format!("vertex index {} out of range", index))),
+            };
+        }
+        Ok(BezierPatch::new(control_points))
+    }).collect()
+}
+
+fn parse_error(line: usize, message: &str) -> PatchError {
+    PatchError::Parse {line, message: message.to_string()}
+}
+
+fn read_count<'a>(lines: &mut impl Iterator<Item = (usize, &'a str)>, what: &str) -> Result<(usize, usize), PatchError> {
+    match lines.next() {
+        Some((line, text)) => match text.trim_end_matches(',').parse() {
+            Ok(count) => Ok((line, count)),
+            Err(_) => Err(parse_error(line, &format!("invalid {} '{}'", what, text))),
+        },
+        None => Err(parse_error(0, &format!("missing {}", what))),
+    }
+}
+
+// The line number of the preceding count is reported when the file ends early.
+fn read_values<'a, T: std::str::FromStr>(
+    lines: &mut impl Iterator<Item = (usize, &'a str)>,
+    expected: usize,
+    what: &str,
+    count_line: usize
+) -> Result<(usize, Vec<T>), PatchError> {
+    let (line, text) = lines.next()
+        .ok_or_else(|| parse_error(count_line, &format!("file ends before every {} is listed", what)))?;
+    let values: Vec<T> = text.split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|token| !token.is_empty())
+        .map(|token| token.parse().map_err(|_| parse_error(line, &format!("invalid {} value '{}'", what, token))))
+        .collect::<Result<_, _>>()?;
+    if values.len() != expected {
+        return Err(parse_error(line, &format!("expected {} values for a {}, found {}", expected, what, values.len())));
+    }
+    Ok((line, values))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FLAT_PATCH: &str = "1
+1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16
+
+16
+0.0, 0.0, 0.0
+1.0, 0.0, 0.0
+2.0, 0.0, 0.0
+3.0, 0.0, 0.0
+0.0, 0.0, 1.0
+1.0, 0.0, 1.0
+2.0, 0.0, 1.0
+3.0, 0.0, 1.0
+0.0, 0.0, 2.0
+1.0, 0.0, 2.0
+2.0, 0.0, 2.0
+3.0, 0.0, 2.0
+0.0, 0.0, 3.0
+1.0, 0.0, 3.0
+2.0, 0.0, 3.0
+3.0, 0.0, 3.0
+";
+
+    #[test]
+    fn test_reading_patches() {
+        let patches = read_patches(FLAT_PATCH).unwrap();
+        assert_eq!(patches.len(), 1);
+        assert_eq!(patches[0].get_control_point(0, 0), point(0.0, 0.0, 0.0));
+        assert_eq!(patches[0].get_control_point(1, 2), point(2.0, 0.0, 1.0));
+        assert_eq!(patches[0].get_control_point(3, 3), point(3.0, 0.0, 3.0));
+    }
+
+    #[test]
+    fn test_whitespace_separated_patches() {
+        let text = FLAT_PATCH.replace(", ", " ");
+        assert_eq!(read_patches(&text).unwrap(), read_patches(FLAT_PATCH).unwrap());
+    }
+
+    #[test]
+    fn test_patches_tessellate_into_group() {
+        let g = parse_patches(FLAT_PATCH, 3).unwrap();
+        assert_eq!(g.elements().len(), 18);
+    }
+
+    #[test]
+    fn test_patch_errors_report_line() {
+        let bad_index = FLAT_PATCH.replacen("16\n", "17\n", 1);
+        match read_patches(&bad_index) {
+            Err(PatchError::Parse {line, ..}) => assert_eq!(line, 2),
+            other => panic!("unexpected result {:?}", other),
+        }
+        let short_vertex = FLAT_PATCH.replace("2.0, 0.0, 1.0", "2.0, 0.0");
+        match read_patches(&short_vertex) {
+            Err(PatchError::Parse {line, ..}) => assert_eq!(line, 11),
+            other => panic!("unexpected result {:?}", other),
+        }
+        let truncated: String = FLAT_PATCH.lines().take(10).collect::<Vec<_>>().join("\n");
+        match read_patches(&truncated) {
+            Err(PatchError::Parse {line, ..}) => assert_eq!(line, 4),
+            other => panic!("unexpected result {:?}", other),
+        }
+        assert!(read_patches("one\n").is_err());
+        assert!(read_patches(&FLAT_PATCH.replacen("1\n", "18446744073709551615\n", 1)).is_err());
+    }
+}
@@ -0,0 +1,574 @@
+use crate::prelude::*;
+
+// Where a malformed PLY file went wrong: header and ASCII body errors carry a 1-based line,
+// binary body errors a byte offset.
+#[derive(Debug)]
+pub enum PlyError {
+    Io(std::io::Error),
+    Header {line: usize, message: String},
+    Ascii {line: usize, message: String},
+    Binary {offset: usize, message: String},
+    InvalidFace {face: usize, vertex: f64},
+}
+
+impl std::fmt::Display for PlyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            PlyError::Io(err) => write!(f, "{}", err),
+            PlyError::Header {line, message} => write!(f, "header line {}: {}", line, message),
+            PlyError::Ascii {line, message} => write!(f, "line {}: {}", line, message),
+            PlyError::Binary {offset, message} => write!(f, "byte {}: {}", offset, message),
+            PlyError::InvalidFace {face, vertex} => write!(f, "face {} refers to missing vertex {}", face, vertex),
+        }
+    }
+}
+
+impl std::error::Error for PlyError {}
+
+impl From<std::io::Error> for PlyError {
+    fn from(err: std::io::Error) -> Self {
+        PlyError::Io(err)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Format {
+    Ascii,
+    BinaryLittleEndian,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum ScalarType {
+    Int8,
+    UInt8,
+    Int16,
+    UInt16,
+    Int32,
+    UInt32,
+    Float32,
+    Float64,
+}
+
+impl ScalarType {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "char" | "int8" => Some(ScalarType::Int8),
+            "uchar" | "uint8" => Some(ScalarType::UInt8),
+            "short" | "int16" => Some(ScalarType::Int16),
+            "ushort" | "uint16" => Some(ScalarType::UInt16),
+            "int" | "int32" => Some(ScalarType::Int32),
+            "uint" | "uint32" => Some(ScalarType::UInt32),
+            "float" | "float32" => Some(ScalarType::Float32),
+            "double" | "float64" => Some(ScalarType::Float64),
+            _ => None,
+        }
+    }
+
+    fn size(&self) -> usize {
+        match self {
+            ScalarType::Int8 | ScalarType::UInt8 => 1,
+            ScalarType::Int16 | ScalarType::UInt16 => 2,
+            ScalarType::Int32 | ScalarType::UInt32 | ScalarType::Float32 => 4,
+            ScalarType::Float64 => 8,
+        }
+    }
+
+    // Integer colour channels are scaled by their type's range, float channels are already 0..1.
+    fn color_scale(&self) -> f64 {
+        match self {
+            ScalarType::Int8 | ScalarType::UInt8 => 255.0,
+            ScalarType::Int16 | ScalarType::UInt16 => 65535.0,
+            ScalarType::Int32 | ScalarType::UInt32 => u32::MAX as f64,
+            ScalarType::Float32 | ScalarType::Float64 => 1.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+enum PropertyKind {
+    Scalar(ScalarType),
+    List(ScalarType, ScalarType),
+}
+
+#[derive(Clone, Debug)]
+struct Property {
+    name: String,
+    kind: PropertyKind,
+}
+
+#[derive(Clone, Debug)]
+struct Element {
+    line: usize,
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+impl Element {
+    fn is_list(property: &Property) -> bool {
+        matches!(property.kind, PropertyKind::List(..))
+    }
+
+    // The position of a scalar property among the record's scalars, with its type. Naming a list
+    // property where a single value is needed is a header error.
+    fn scalar_slot(&self, name: &str) -> Result<Option<(usize, ScalarType)>, PlyError> {
+        let index = match self.properties.iter().position(|p| p.name == name) {
+            Some(index) => index,
+            None => return Ok(None),
+        };
+        match self.properties[index].kind {
+            PropertyKind::Scalar(scalar) => {
+                let slot = self.properties[..index].iter().filter(|p| !Element::is_list(p)).count();
+                Ok(Some((slot, scalar)))
+            },
+            PropertyKind::List(..) => {
+                Err(PlyError::Header {line: self.line, message: format!("{} property '{}' must not be a list", self.name, name)})
+            },
+        }
+    }
+
+    fn list_slot(&self, name: &str) -> Result<Option<usize>, PlyError> {
+        let index = match self.properties.iter().position(|p| p.name == name) {
+            Some(index) => index,
+            None => return Ok(None),
+        };
+        if !Element::is_list(&self.properties[index]) {
+            return Err(PlyError::Header {line: self.line, message: format!("{} property '{}' must be a list", self.name, name)});
+        }
+        Ok(Some(self.properties[..index].iter().filter(|p| Element::is_list(p)).count()))
+    }
+}
+
+// Every record of one element, read into flat buffers rather than a Vec per property: each record
+// has the same number of scalars, and list items are kept end to end with the span of each list.
+struct ElementData {
+    scalars_per_record: usize,
+    lists_per_record: usize,
+    scalars: Vec<f64>,
+    list_items: Vec<f64>,
+    lists: Vec<(usize, usize)>,
+}
+
+impl ElementData {
+    fn scalar(&self, record: usize, slot: usize) -> f64 {
+        self.scalars[record * self.scalars_per_record + slot]
+    }
+
+    fn list(&self, record: usize, slot: usize) -> &[f64] {
+        let (start, length) = self.lists[record * self.lists_per_record + slot];
+        &self.list_items[start..start + length]
+    }
+}
+
+trait ValueReader {
+    fn read(&mut self, scalar: ScalarType) -> Result<f64, PlyError>;
+
+    // Counts come from the file, so they aren't trusted for reservations; a corrupt one runs out of
+    // input instead.
+    fn read_element(&mut self, element: &Element) -> Result<ElementData, PlyError> {
+        let lists_per_record = element.properties.iter().filter(|p| Element::is_list(p)).count();
+        let mut data = ElementData {
+            scalars_per_record: element.properties.len() - lists_per_record,
+            lists_per_record,
+            scalars: vec![],
+            list_items: vec![],
+            lists: vec![],
+        };
+        for _ in 0..element.count {
+            for property in &element.properties {
+                match property.kind {
+                    PropertyKind::Scalar(scalar) => data.scalars.push(self.read(scalar)?),
+                    PropertyKind::List(count_type, item_type) => {
+                        let count = self.read(count_type)? as usize;
+                        let start = data.list_items.len();
+                        for _ in 0..count {
+                            data.list_items.push(self.read(item_type)?);
+                        }
+                        data.lists.push((start, count));
+                    },
+                }
+            }
+        }
+        Ok(data)
+    }
+}
+
+struct AsciiReader<'a> {
+    lines: std::str::Lines<'a>,
+    tokens: Vec<&'a str>,
+    next_token: usize,
+    line: usize,
+}
+
+impl<'a> ValueReader for AsciiReader<'a> {
+    fn read(&mut self, _scalar: ScalarType) -> Result<f64, PlyError> {
+        while self.next_token >= self.tokens.len() {
+            match self.lines.next() {
+                Some(line) => {
+                    self.line += 1;
+                    self.tokens = line.split_whitespace().collect();
+                    self.next_token = 0;
+                },
+                None => return Err(PlyError::Ascii {line: self.line, message: "unexpected end of file".to_string()}),
+            }
+        }
+        let token = self.tokens[self.next_token];
+        self.next_token += 1;
+        token.parse().map_err(|_| PlyError::Ascii {line: self.line, message: format!("invalid number '{}'", token)})
+    }
+}
+
+struct BinaryReader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> ValueReader for BinaryReader<'a> {
+    fn read(&mut self, scalar: ScalarType) -> Result<f64, PlyError> {
+        let size = scalar.size();
+        if self.offset + size > self.data.len() {
+            return Err(PlyError::Binary {offset: self.data.len(), message: "unexpected end of file".to_string()});
+        }
+        let bytes = &self.data[self.offset..self.offset + size];
+        self.offset += size;
+        let value = match scalar {
+            ScalarType::Int8 => bytes[0] as i8 as f64,
+            ScalarType::UInt8 => bytes[0] as f64,
+            ScalarType::Int16 => i16::from_le_bytes([bytes[0], bytes[1]]) as f64,
+            ScalarType::UInt16 => u16::from_le_bytes([bytes[0], bytes[1]]) as f64,
+            ScalarType::Int32 => i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64,
+            ScalarType::UInt32 => u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64,
+            ScalarType::Float32 => f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64,
+            ScalarType::Float64 => {
+                let mut array = [0u8; 8];
+                array.copy_from_slice(bytes);
+                f64::from_le_bytes(array)
+            },
+        };
+        Ok(value)
+    }
+}
+
+struct Header {
+    format: Format,
+    elements: Vec<Element>,
+    lines: usize,
+    bytes: usize,
+}
+
+fn header_error(line: usize, message: &str) -> PlyError {
+    PlyError::Header {line, message: message.to_string()}
+}
+
+fn parse_header(data: &[u8]) -> Result<Header, PlyError> {
+    let mut format = None;
+    let mut elements: Vec<Element> = vec![];
+    let mut offset = 0;
+    let mut line_number = 0;
+
+    loop {
+        let end = match data[offset..].iter().position(|&b| b == b'\n') {
+            Some(end) => offset + end,
+            None => return Err(header_error(line_number + 1, "missing end_header")),
+        };
+        let line = std::str::from_utf8(&data[offset..end])
+            .map_err(|_| header_error(line_number + 1, "header is not text"))?;
+        offset = end + 1;
+        line_number += 1;
+
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        if line_number == 1 {
+            if tokens != ["ply"] {
+                return Err(header_error(line_number, "not a PLY file"));
+            }
+            continue;
+        }
+        match tokens.as_slice() {
+            ["format", "ascii", _] => format = Some(Format::Ascii),
+            ["format", "binary_little_endian", _] => format = Some(Format::BinaryLittleEndian),
+            ["format", other, _] => {
+                return Err(PlyError::Header {line: line_number, message: format!("unsupported format '{}'", other)});
+            },
+            ["comment", ..] | ["obj_info", ..] | [] => (),
+            ["element", name, count] => {
+                let count = count.parse().map_err(|_| header_error(line_number, "invalid element count"))?;
+                elements.push(Element {line: line_number, name: name.to_string(), count, properties: vec![]});
+            },
+            ["property", "list", count_type, item_type, name] => {
+                let count_type = ScalarType::parse(count_type).ok_or_else(|| header_error(line_number, "unknown property type"))?;
+                let item_type = ScalarType::parse(item_type).ok_or_else(|| header_error(line_number, "unknown property type"))?;
+                let element = elements.last_mut().ok_or_else(|| header_error(line_number, "property outside of an element"))?;
+                element.properties.push(Property {name: name.to_string(), kind: PropertyKind::List(count_type, item_type)});
+            },
+            ["property", scalar, name] => {
+                let scalar = ScalarType::parse(scalar).ok_or_else(|| header_error(line_number, "unknown property type"))?;
+                let element = elements.last_mut().ok_or_else(|| header_error(line_number, "property outside of an element"))?;
+                element.properties.push(Property {name: name.to_string(), kind: PropertyKind::Scalar(scalar)});
+            },
+            ["end_header"] => break,
+            _ => return Err(PlyError::Header {line: line_number, message: format!("unexpected '{}'", line.trim())}),
+        }
+    }
+
+    let format = format.ok_or_else(|| header_error(line_number, "missing format"))?;
+    Ok(Header {format, elements, lines: line_number, bytes: offset})
+}
+
+pub fn parse_ply_file(path: &str) -> Result<Group, PlyError> {
+    parse_ply(&std::fs::read(path)?)
+}
+
+// Builds a group of triangles from the vertex and face elements, fan-triangulating polygons.
+// Vertex normals produce smooth triangles and vertex colours a vertex colour pattern.
+pub fn parse_ply(data: &[u8]) -> Result<Group, PlyError> {
+    let header = parse_header(data)?;
+    let mut elements = vec![];
+    match header.format {
+        Format::Ascii => {
+            let text = std::str::from_utf8(&data[header.bytes..])
+                .map_err(|_| PlyError::Ascii {line: header.lines + 1, message: "body is not text".to_string()})?;
+            let mut reader = AsciiReader {
+                lines: text.lines(),
+                tokens: vec![],
+                next_token: 0,
+                line: header.lines,
+            };
+            for element in &header.elements {
+                elements.push(reader.read_element(element)?);
+            }
+        },
+        Format::BinaryLittleEndian => {
+            let mut reader = BinaryReader {data, offset: header.bytes};
+            for element in &header.elements {
+                elements.push(reader.read_element(element)?);
+            }
+        },
+    }
+    build_group(&header.elements, &elements)
+}
+
+fn build_group(elements: &[Element], data: &[ElementData]) -> Result<Group, PlyError> {
+    let mut group = Group::new_empty();
+    let vertex_element = match elements.iter().position(|e| e.name == "vertex") {
+        Some(index) => index,
+        None => return Ok(group),
+    };
+    let vertex = &elements[vertex_element];
+    let lookup = |names: [&str; 3]| -> Result<Option<[(usize, ScalarType); 3]>, PlyError> {
+        match (vertex.scalar_slot(names[0])?, vertex.scalar_slot(names[1])?, vertex.scalar_slot(names[2])?) {
+            (Some(a), Some(b), Some(c)) => Ok(Some([a, b, c])),
+            _ => Ok(None),
+        }
+    };
+    let position = lookup(["x", "y", "z"])?
+        .ok_or_else(|| header_error(vertex.line, "vertex element has no x, y and z properties"))?;
+    let normal = lookup(["nx", "ny", "nz"])?;
+    let color_channels = lookup(["red", "green", "blue"])?;
+
+    let vertex_data = &data[vertex_element];
+    let vertices: Vec<(Tuple, Option<Tuple>, Option<Color>)> = (0..vertex.count)
+        .map(|record| {
+            let value = |(slot, _): (usize, ScalarType)| vertex_data.scalar(record, slot);
+            let p = point(value(position[0]), value(position[1]), value(position[2]));
+            let n = normal.map(|i| vector(value(i[0]), value(i[1]), value(i[2])));
+            let c = color_channels.map(|i| {
+                let scale = i[0].1.color_scale();
+                color(value(i[0]) / scale, value(i[1]) / scale, value(i[2]) / scale)
+            });
+            (p, n, c)
+        })
+        .collect();
+
+    let face_element = match elements.iter().position(|e| e.name == "face") {
+        Some(index) => index,
+        None => return Ok(group),
+    };
+    let face = &elements[face_element];
+    let indices = match face.list_slot("vertex_indices")? {
+        Some(slot) => slot,
+        None => face.list_slot("vertex_index")?
+            .ok_or_else(|| header_error(face.line, "face element has no vertex_indices property"))?,
+    };
+
+    // Every triangle shares one mesh. Vertex normals sit at the same index as their vertex, and flat
+    // faces get a facet normal appended after them.
+    let mut mesh = Mesh::new();
+    for &(p, n, _) in &vertices {
+        mesh.add_point(p);
+        if let Some(n) = n {
+            mesh.add_normal(n);
+        }
+    }
+    let mut faces = vec![];
+    for number in 0..face.count {
+        let mut corners = vec![];
+        for &index in data[face_element].list(number, indices) {
+            // Indices are read as f64, so negative or fractional ones have to be caught before the cast.
+            if index >= 0.0 && index.fract() == 0.0 && (index as usize) < vertices.len() {
+                corners.push(index as usize);
+            } else {
+                return Err(PlyError::InvalidFace {face: number, vertex: index});
+            }
+        }
+        for i in 1..corners.len().saturating_sub(1) {
+            let points = [corners[0], corners[i], corners[i + 1]];
+            let [p1, p2, p3] = points.map(|index| vertices[index].0);
+            let facet = match normal {
+                Some(_) => None,
+                None => Some(mesh.add_normal((p3 - p1).xprod(p2 - p1).normalize())),
+            };
+            let colors = points.map(|index| vertices[index].2);
+            let pattern = match colors {
+                [Some(c1), Some(c2), Some(c3)] => Some(vertex_color(c1, c2, c3)),
+                _ => None,
+            };
+            faces.push((points, facet, pattern));
+        }
+    }
+
+    let mesh = Arc::new(mesh);
+    for (points, facet, pattern) in faces {
+        let mut triangle = match facet {
+            Some(facet) => triangles::from_mesh(&mesh, points, facet),
+            None => smooth_triangles::from_mesh(&mesh, points, points),
+        };
+        if let Some(pattern) = pattern {
+            triangle.set_pattern(pattern);
+        }
+        group.add_object(triangle);
+    }
+    Ok(group)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COLORED_QUAD: &str = "ply
+format ascii 1.0
+comment a unit square with a colour per corner
+element vertex 4
+property float x
+property float y
+property float z
+property uchar red
+property uchar green
+property uchar blue
+element face 1
+property list uchar int vertex_indices
+end_header
+0 0 0 255 0 0
+1 0 0 0 255 0
+1 1 0 0 0 255
+0 1 0 255 255 255
+4 0 1 2 3
+";
+
+    fn binary_ply(vertices: &[[f32; 3]], faces: &[[i32; 3]]) -> Vec<u8> {
+        let header = format!(
+            "ply\nformat binary_little_endian 1.0\nelement vertex {}\nproperty float x\nproperty float y\n\
+             property float z\nelement face {}\nproperty list uchar int vertex_indices\nend_header\n",
+            vertices.len(), faces.len());
+        let mut data = header.into_bytes();
+        for vertex in vertices {
+            for value in vertex {
+                data.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+        for face in faces {
+            data.push(3);
+            for index in face {
+                data.extend_from_slice(&index.to_le_bytes());
+            }
+        }
+        data
+    }
+
+    #[test]
+    fn test_ascii_ply_with_colors() {
+        let g = parse_ply(COLORED_QUAD.as_bytes()).unwrap();
+        assert_eq!(g.elements().len(), 2);
+
+        let first = g.elements()[0].get_object().unwrap();
+        assert_eq!(first.get_pattern().get_pattern_type(), PatternType::VertexColor);
+        assert_eq!(first.pattern_at_object(point(0.0, 0.0, 0.0)), color(1.0, 0.0, 0.0));
+        assert_eq!(first.pattern_at_object(point(1.0, 0.0, 0.0)), color(0.0, 1.0, 0.0));
+        assert_eq!(first.pattern_at_object(point(0.5, 0.0, 0.0)), color(0.5, 0.5, 0.0));
+
+        let second = g.elements()[1].get_object().unwrap();
+        assert_eq!(second.pattern_at_object(point(0.0, 1.0, 0.0)), white());
+    }
+
+    #[test]
+    fn test_ascii_ply_with_normals() {
+        let ply = "ply\nformat ascii 1.0\nelement vertex 3\nproperty float x\nproperty float y\nproperty float z\n\
+                   property float nx\nproperty float ny\nproperty float nz\nelement face 1\n\
+                   property list uchar uint vertex_index\nend_header\n\
+                   0 1 0 0 1 0\n-1 0 0 -1 0 0\n1 0 0 1 0 0\n3 0 1 2\n";
+        let g = parse_ply(ply.as_bytes()).unwrap();
+        let t = g.elements()[0].get_object().unwrap();
+        assert!(matches!(t.shape, Shape::SmoothTriangle {..}));
+        assert_eq!(smooth_triangles::normal_at(t, 1.0, 0.0), vector(-1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn test_binary_ply() {
+        let data = binary_ply(&[[0.0, 1.0, 0.0], [-1.0, 0.0, 0.0], [1.0, 0.0, 0.0]], &[[0, 1, 2]]);
+        let g = parse_ply(&data).unwrap();
+        assert_eq!(g.elements().len(), 1);
+        let t = g.elements()[0].get_object().unwrap();
+        assert!(matches!(t.shape, Shape::Triangle {..}));
+        let [p1, _, p3] = triangles::corners(t);
+        assert_eq!(p1, point(0.0, 1.0, 0.0));
+        assert_eq!(p3, point(1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn test_ply_errors() {
+        let mut data = binary_ply(&[[0.0, 1.0, 0.0], [-1.0, 0.0, 0.0], [1.0, 0.0, 0.0]], &[[0, 1, 2]]);
+        let length = data.len() - 3;
+        data.truncate(length);
+        match parse_ply(&data) {
+            Err(PlyError::Binary {offset, ..}) => assert_eq!(offset, length),
+            other => panic!("Expected a binary error, got {:?}", other),
+        }
+
+        match parse_ply(COLORED_QUAD.replace("1 1 0 0 0 255", "1 1 0 0 0 blue").as_bytes()) {
+            Err(PlyError::Ascii {line, ..}) => assert_eq!(line, 16),
+            other => panic!("Expected an ASCII error, got {:?}", other),
+        }
+
+        match parse_ply(COLORED_QUAD.replace("ascii", "binary_big_endian").as_bytes()) {
+            Err(PlyError::Header {line, ..}) => assert_eq!(line, 2),
+            other => panic!("Expected a header error, got {:?}", other),
+        }
+
+        match parse_ply(COLORED_QUAD.replace("4 0 1 2 3", "3 0 1 7").as_bytes()) {
+            Err(PlyError::InvalidFace {face, vertex}) => assert_eq!((face, vertex), (0, 7.0)),
+            other => panic!("Expected an invalid face, got {:?}", other),
+        }
+
+        match parse_ply(COLORED_QUAD.replace("4 0 1 2 3", "3 0 1 -1").as_bytes()) {
+            Err(PlyError::InvalidFace {face, vertex}) => assert_eq!((face, vertex), (0, -1.0)),
+            other => panic!("Expected an invalid face, got {:?}", other),
+        }
+
+        // An empty list where a coordinate should be would otherwise index past the end of the record.
+        let list_position = "ply\nformat ascii 1.0\nelement vertex 1\nproperty float x\n\
+                             property list uchar float y\nproperty float z\nend_header\n0 0 0\n";
+        match parse_ply(list_position.as_bytes()) {
+            Err(PlyError::Header {line, ..}) => assert_eq!(line, 3),
+            other => panic!("Expected a header error, got {:?}", other),
+        }
+
+        match parse_ply(COLORED_QUAD.replace("property list uchar int vertex_indices", "property int vertex_indices").as_bytes()) {
+            Err(PlyError::Header {line, ..}) => assert_eq!(line, 11),
+            other => panic!("Expected a header error, got {:?}", other),
+        }
+
+        match parse_ply(COLORED_QUAD.replace("4 0 1 2 3", "3 0 1 1.5").as_bytes()) {
+            Err(PlyError::InvalidFace {face, vertex}) => assert_eq!((face, vertex), (0, 1.5)),
+            other => panic!("Expected an invalid face, got {:?}", other),
+        }
+    }
+}
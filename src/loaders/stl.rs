@@ -0,0 +1,296 @@
+use crate::prelude::*;
+
+const HEADER_LEN: usize = 80;
+const FACET_LEN: usize = 50;
+
+// Where a malformed STL file went wrong: a byte offset for binary files, a 1-based line for ASCII ones.
+#[derive(Debug)]
+pub enum StlError {
+    Io(std::io::Error),
+    Binary {offset: usize, message: String},
+    Ascii {line: usize, message: String},
+}
+
+impl std::fmt::Display for StlError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            StlError::Io(err) => write!(f, "{}", err),
+            StlError::Binary {offset, message} => write!(f, "byte {}: {}", offset, message),
+            StlError::Ascii {line, message} => write!(f, "line {}: {}", line, message),
+        }
+    }
+}
+
+impl std::error::Error for StlError {}
+
+impl From<std::io::Error> for StlError {
+    fn from(err: std::io::Error) -> Self {
+        StlError::Io(err)
+    }
+}
+
+pub fn parse_stl_file(path: &str) -> Result<Group, StlError> {
+    parse_stl(&std::fs::read(path)?)
+}
+
+// Binary files may also begin with "solid", so the data is only treated as ASCII when the facet count
+// disagrees with the length and the start of the file is plain text.
+pub fn parse_stl(data: &[u8]) -> Result<Group, StlError> {
+    if !binary_length_matches(data) && data.starts_with(b"solid") && looks_like_text(data) {
+        match std::str::from_utf8(data) {
+            Ok(text) => parse_ascii(text),
+            Err(err) => Err(StlError::Binary {offset: err.valid_up_to(), message: "invalid text in ASCII STL".to_string()}),
+        }
+    } else {
+        parse_binary(data)
+    }
+}
+
+fn binary_length_matches(data: &[u8]) -> bool {
+    data.len() >= HEADER_LEN + 4 && data.len() == HEADER_LEN + 4 + FACET_LEN * read_u32(data, HEADER_LEN) as usize
+}
+
+fn looks_like_text(data: &[u8]) -> bool {
+    data.iter()
+        .take(HEADER_LEN + 4 + FACET_LEN)
+        .all(|b| b.is_ascii_graphic() || b.is_ascii_whitespace())
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([data[offset], data[offset + 1], data[offset + 2], data[offset + 3]])
+}
+
+fn read_f32(data: &[u8], offset: usize) -> f64 {
+    f32::from_le_bytes([data[offset], data[offset + 1], data[offset + 2], data[offset + 3]]) as f64
+}
+
+fn read_triple(data: &[u8], offset: usize) -> (f64, f64, f64) {
+    (read_f32(data, offset), read_f32(data, offset + 4), read_f32(data, offset + 8))
+}
+
+fn parse_binary(data: &[u8]) -> Result<Group, StlError> {
+    if data.len() < HEADER_LEN + 4 {
+        return Err(StlError::Binary {offset: data.len(), message: "file ends inside the header".to_string()});
+    }
+    let count = read_u32(data, HEADER_LEN) as usize;
+    let (mut mesh, mut facets) = (Mesh::new(), vec![]);
+    for i in 0..count {
+        let offset = HEADER_LEN + 4 + i * FACET_LEN;
+        if offset + FACET_LEN > data.len() {
+            return Err(StlError::Binary {
+                offset: data.len(),
+                message: format!("file ends inside facet {} of {}", i + 1, count),
+            });
+        }
+        let (nx, ny, nz) = read_triple(data, offset);
+        let mut vertices = vec![];
+        for j in 0..3 {
+            let (x, y, z) = read_triple(data, offset + 12 * (j + 1));
+            vertices.push(point(x, y, z));
+        }
+        add_facet(&mut mesh, &mut facets, vector(nx, ny, nz), &vertices);
+    }
+    if data.len() > HEADER_LEN + 4 + count * FACET_LEN {
+        return Err(StlError::Binary {
+            offset: HEADER_LEN + 4 + count * FACET_LEN,
+            message: format!("unexpected data after {} facets", count),
+        });
+    }
+    Ok(build_group(mesh, facets))
+}
+
+// Uses the stored normal unless the file left it zeroed, as many exporters do. STL lists vertices
+// anticlockwise seen from outside, so the fallback is e1 × e2 rather than the triangle default.
+fn add_facet(mesh: &mut Mesh, facets: &mut Vec<([usize; 3], usize)>, normal: Tuple, vertices: &[Tuple]) {
+    let corners: Vec<usize> = vertices.iter().map(|&vertex| mesh.add_point(vertex)).collect();
+    let stored = if normal.magnitude() > EPSILON { Some(mesh.add_normal(normal.normalize())) } else { None };
+    for i in 1..corners.len() - 1 {
+        let points = [corners[0], corners[i], corners[i + 1]];
+        let normal = stored.unwrap_or_else(|| {
+            let [p1, p2, p3] = points.map(|index| mesh.get_point(index));
+            mesh.add_normal((p2 - p1).xprod(p3 - p1).normalize())
+        });
+        facets.push((points, normal));
+    }
+}
+
+// The facets all share the mesh they were read into.
+fn build_group(mesh: Mesh, facets: Vec<([usize; 3], usize)>) -> Group {
+    let mesh = Arc::new(mesh);
+    let mut group = Group::new_empty();
+    for (points, normal) in facets {
+        group.add_object(triangles::from_mesh(&mesh, points, normal));
+    }
+    group
+}
+
+fn ascii_error(line: usize, message: &str) -> StlError {
+    StlError::Ascii {line, message: message.to_string()}
+}
+
+fn parse_numbers(line: usize, args: &[&str]) -> Result<(f64, f64, f64), StlError> {
+    if args.len() != 3 {
+        return Err(ascii_error(line, "expected three numbers"));
+    }
+    let mut values = [0.0; 3];
+    for (value, arg) in values.iter_mut().zip(args) {
+        *value = arg.parse().map_err(|_| StlError::Ascii {line, message: format!("invalid number '{}'", arg)})?;
+    }
+    Ok((values[0], values[1], values[2]))
+}
+
+// Where the ASCII parser is in the solid / facet / outer loop nesting.
+enum AsciiState {
+    Start,
+    Solid,
+    Facet(Tuple),
+    Loop(Tuple, Vec<Tuple>),
+    LoopClosed,
+    End,
+}
+
+fn parse_ascii(text: &str) -> Result<Group, StlError> {
+    let (mut mesh, mut facets) = (Mesh::new(), vec![]);
+    let mut state = AsciiState::Start;
+    let mut last_line = 0;
+
+    for (index, line) in text.lines().enumerate() {
+        let line_number = index + 1;
+        last_line = line_number;
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        if tokens.is_empty() {
+            continue;
+        }
+        state = match (state, tokens[0]) {
+            (AsciiState::Start, "solid") => AsciiState::Solid,
+            (AsciiState::Solid, "facet") => {
+                if tokens.get(1) != Some(&"normal") {
+                    return Err(ascii_error(line_number, "expected 'facet normal'"));
+                }
+                let (x, y, z) = parse_numbers(line_number, &tokens[2..])?;
+                AsciiState::Facet(vector(x, y, z))
+            },
+            (AsciiState::Solid, "endsolid") => AsciiState::End,
+            (AsciiState::Facet(normal), "outer") => {
+                if tokens[1..] != ["loop"] {
+                    return Err(ascii_error(line_number, "expected 'outer loop'"));
+                }
+                AsciiState::Loop(normal, vec![])
+            },
+            (AsciiState::Loop(normal, mut vertices), "vertex") => {
+                let (x, y, z) = parse_numbers(line_number, &tokens[1..])?;
+                vertices.push(point(x, y, z));
+                AsciiState::Loop(normal, vertices)
+            },
+            (AsciiState::Loop(normal, vertices), "endloop") => {
+                if vertices.len() < 3 {
+                    return Err(ascii_error(line_number, "loop has fewer than three vertices"));
+                }
+                add_facet(&mut mesh, &mut facets, normal, &vertices);
+                AsciiState::LoopClosed
+            },
+            (AsciiState::LoopClosed, "endfacet") => AsciiState::Solid,
+            (_, keyword) => {
+                return Err(StlError::Ascii {line: line_number, message: format!("unexpected '{}'", keyword)});
+            },
+        };
+    }
+    match state {
+        AsciiState::End => Ok(build_group(mesh, facets)),
+        _ => Err(ascii_error(last_line, "missing endsolid")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TETRAHEDRON: &str = "solid tetra
+  facet normal 0 0 -1
+    outer loop
+      vertex 0 0 0
+      vertex 0 1 0
+      vertex 1 0 0
+    endloop
+  endfacet
+  facet normal 0 0 0
+    outer loop
+      vertex 0 0 0
+      vertex 1 0 0
+      vertex 0 0 1
+    endloop
+  endfacet
+endsolid tetra
+";
+
+    fn binary_stl(facets: &[[f32; 12]]) -> Vec<u8> {
+        let mut data = vec![0u8; HEADER_LEN];
+        data[..5].copy_from_slice(b"solid");
+        data.extend_from_slice(&(facets.len() as u32).to_le_bytes());
+        for facet in facets {
+            for value in facet {
+                data.extend_from_slice(&value.to_le_bytes());
+            }
+            data.extend_from_slice(&[0, 0]);
+        }
+        data
+    }
+
+    fn triangle_normal(holder: &ObjectHolder) -> Tuple {
+        triangles::normal_at(holder.get_object().unwrap())
+    }
+
+    #[test]
+    fn test_ascii_stl() {
+        let g = parse_stl(TETRAHEDRON.as_bytes()).unwrap();
+        assert_eq!(g.elements().len(), 2);
+        assert_eq!(triangle_normal(&g.elements()[0]), vector(0.0, 0.0, -1.0));
+        // A zeroed normal falls back to the outward one given by the anticlockwise winding.
+        assert_eq!(triangle_normal(&g.elements()[1]), vector(0.0, -1.0, 0.0));
+    }
+
+    #[test]
+    fn test_ascii_stl_errors_report_line() {
+        let broken = TETRAHEDRON.replace("vertex 0 1 0", "vertex 0 one 0");
+        match parse_stl(broken.as_bytes()) {
+            Err(StlError::Ascii {line, ..}) => assert_eq!(line, 5),
+            other => panic!("Expected an ASCII error, got {:?}", other),
+        }
+
+        let truncated: String = TETRAHEDRON.lines().take(8).collect::<Vec<&str>>().join("\n");
+        match parse_stl(truncated.as_bytes()) {
+            Err(StlError::Ascii {line, ..}) => assert_eq!(line, 8),
+            other => panic!("Expected an ASCII error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_binary_stl() {
+        let data = binary_stl(&[
+            [0.0, 0.0, 2.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0],
+        ]);
+        let g = parse_stl(&data).unwrap();
+        assert_eq!(g.elements().len(), 2);
+        assert_eq!(triangle_normal(&g.elements()[0]), vector(0.0, 0.0, 1.0));
+        assert_eq!(triangle_normal(&g.elements()[1]), vector(0.0, -1.0, 0.0));
+        let [_, p2, p3] = triangles::corners(g.elements()[1].get_object().unwrap());
+        assert_eq!(p2, point(1.0, 0.0, 0.0));
+        assert_eq!(p3, point(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn test_binary_stl_errors_report_offset() {
+        let mut data = binary_stl(&[[0.0; 12], [0.0; 12]]);
+        data.truncate(HEADER_LEN + 4 + FACET_LEN + 10);
+        match parse_stl(&data) {
+            Err(StlError::Binary {offset, ..}) => assert_eq!(offset, HEADER_LEN + 4 + FACET_LEN + 10),
+            other => panic!("Expected a binary error, got {:?}", other),
+        }
+
+        match parse_stl(&[0u8; 20]) {
+            Err(StlError::Binary {offset, ..}) => assert_eq!(offset, 20),
+            other => panic!("Expected a binary error, got {:?}", other),
+        }
+    }
+}
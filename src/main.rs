@@ -2,7 +2,9 @@ pub mod matrices;
 pub mod scenes;
 pub mod shapes;
 pub mod surfaces;
+pub mod loaders;
 pub mod rays;
+pub mod polynomials;
 pub mod utils;
 mod app;
 
@@ -17,7 +19,9 @@ mod prelude {
     pub use crate::surfaces::{
         patterns::*,
         materials::*,
-        colors::*
+        colors::*,
+        textures::*,
+        uv_maps::*
     };
     pub use crate::scenes::{
         camera::*,
@@ -33,8 +37,17 @@ mod prelude {
         planes::*,
         spheres::*,
         groups::*,
+        csg::*,
+        heightfields::*,
+        instances::*,
+        meshes::*,
+        sdfs::SdfNode,
+        metaballs::Charge,
+        bezier_patches::*,
         objectholders::*,
         traits::*,
+        bounds::*,
+        bvh::*,
     };
     pub use crate::shapes::{
         cones,
@@ -42,19 +55,32 @@ mod prelude {
         cylinders,
         objects,
         planes,
+        disks,
+        quads,
         spheres,
+        triangles,
+        smooth_triangles,
+        tori,
+        sdfs,
+        metaballs,
         groups,
+        csg,
+        heightfields,
+        instances,
         objectholders,
         traits,
     };
     pub use crate::rays::*;
+    pub use crate::polynomials::*;
     pub use crate::utils::*;
     pub use crate::{
         matrices,
         scenes,
         shapes,
         surfaces,
+        loaders,
         rays,
+        polynomials,
         utils
     };
     pub use std::f64::consts::{
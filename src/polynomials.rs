@@ -0,0 +1,203 @@
+// Real roots of low-degree polynomials, given highest-degree coefficient first and returned in
+// ascending order. Shared by the implicit surfaces whose ray equations are polynomials in t.
+
+pub fn solve_quadratic(a: f64, b: f64, c: f64) -> Vec<f64> {
+    if a.abs() < 1e-12 {
+        if b.abs() < 1e-12 {
+            return vec![];
+        }
+        return vec![-c / b];
+    }
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return vec![];
+    }
+    // Avoids the cancellation in -b + sqrt(discriminant) when b is large.
+    let q = -0.5 * (b + b.signum() * discriminant.sqrt());
+    let mut roots = if q == 0.0 {
+        vec![0.0, 0.0]
+    } else {
+        vec![q / a, c / q]
+    };
+    roots.sort_by(|x, y| x.partial_cmp(y).unwrap());
+    roots
+}
+
+pub fn solve_cubic(a: f64, b: f64, c: f64, d: f64) -> Vec<f64> {
+    if a.abs() < 1e-12 {
+        return solve_quadratic(b, c, d);
+    }
+    let (b, c, d) = (b / a, c / a, d / a);
+
+    // Substituting x = t - b/3 gives the depressed cubic t^3 + pt + q.
+    let shift = b / 3.0;
+    let p = c - b * b / 3.0;
+    let q = 2.0 * b * b * b / 27.0 - b * c / 3.0 + d;
+    let discriminant = q * q / 4.0 + p * p * p / 27.0;
+
+    let mut roots = if discriminant.abs() < 1e-14 {
+        let u = (-q / 2.0).cbrt();
+        vec![2.0 * u - shift, -u - shift]
+    } else if discriminant > 0.0 {
+        let sqrt_disc = discriminant.sqrt();
+        vec![(-q / 2.0 + sqrt_disc).cbrt() + (-q / 2.0 - sqrt_disc).cbrt() - shift]
+    } else {
+        let r = (-p / 3.0).sqrt();
+        let phi = (-q / (2.0 * r * r * r)).clamp(-1.0, 1.0).acos();
+        (0..3)
+            .map(|k| 2.0 * r * ((phi + 2.0 * std::f64::consts::PI * k as f64) / 3.0).cos() - shift)
+            .collect()
+    };
+    roots.sort_by(|x, y| x.partial_cmp(y).unwrap());
+    roots
+}
+
+// Ferrari's method through the resolvent cubic, with every root then polished by Newton's method
+// against the original polynomial to recover the precision lost along the way.
+pub fn solve_quartic(a: f64, b: f64, c: f64, d: f64, e: f64) -> Vec<f64> {
+    if a.abs() < 1e-12 {
+        return solve_cubic(b, c, d, e);
+    }
+    let (b, c, d, e) = (b / a, c / a, d / a, e / a);
+
+    // Substituting x = y - b/4 gives the depressed quartic y^4 + py^2 + qy + r.
+    let shift = b / 4.0;
+    let p = c - 3.0 * b * b / 8.0;
+    let q = d - b * c / 2.0 + b * b * b / 8.0;
+    let r = e - b * d / 4.0 + b * b * c / 16.0 - 3.0 * b * b * b * b / 256.0;
+
+    let mut depressed_roots = vec![];
+    if q.abs() < 1e-12 {
+        for z in solve_quadratic(1.0, p, r) {
+            if z >= 0.0 {
+                depressed_roots.push(z.sqrt());
+                depressed_roots.push(-z.sqrt());
+            } else if z > -1e-9 {
+                depressed_roots.push(0.0);
+            }
+        }
+    } else {
+        let m = solve_cubic(1.0, p, p * p / 4.0 - r, -q * q / 8.0)
+            .into_iter()
+            .fold(f64::NEG_INFINITY, f64::max);
+        if m <= 0.0 {
+            return vec![];
+        }
+        let s = (2.0 * m).sqrt();
+        depressed_roots.extend(solve_quadratic(1.0, s, p / 2.0 + m - q / (2.0 * s)));
+        depressed_roots.extend(solve_quadratic(1.0, -s, p / 2.0 + m + q / (2.0 * s)));
+    }
+
+    let mut roots: Vec<f64> = depressed_roots
+        .into_iter()
+        .map(|y| polish_quartic_root(y - shift, b, c, d, e))
+        .collect();
+    roots.sort_by(|x, y| x.partial_cmp(y).unwrap());
+    roots
+}
+
+fn polish_quartic_root(mut x: f64, b: f64, c: f64, d: f64, e: f64) -> f64 {
+    for _ in 0..3 {
+        let value = (((x + b) * x + c) * x + d) * x + e;
+        let slope = ((4.0 * x + 3.0 * b) * x + 2.0 * c) * x + d;
+        if slope.abs() < 1e-12 {
+            break;
+        }
+        x -= value / slope;
+    }
+    x
+}
+
+// Real roots in [low, high] of a polynomial of any degree. Between consecutive roots of its derivative
+// the polynomial is monotonic, so each of those pieces holds at most one root and bisection finds it.
+// Turning points are located exactly this way too, so a pair of roots only a hair apart isn't missed.
+pub fn solve_polynomial_in(coefficients: &[f64], low: f64, high: f64) -> Vec<f64> {
+    let degree = coefficients.len().saturating_sub(1);
+    if degree == 0 {
+        return vec![];
+    }
+    let derivative: Vec<f64> = coefficients[..degree]
+        .iter()
+        .enumerate()
+        .map(|(i, c)| c * (degree - i) as f64)
+        .collect();
+    let mut ends = vec![low];
+    ends.extend(solve_polynomial_in(&derivative, low, high));
+    ends.push(high);
+
+    let negative = |x: f64| evaluate(coefficients, x) < 0.0;
+    ends.windows(2)
+        .filter(|piece| negative(piece[0]) != negative(piece[1]))
+        .map(|piece| {
+            let (mut low, mut high) = (piece[0], piece[1]);
+            let low_negative = negative(low);
+            loop {
+                let middle = (low + high) / 2.0;
+                if middle <= low || middle >= high {
+                    return middle;
+                }
+                if negative(middle) == low_negative {
+                    low = middle;
+                } else {
+                    high = middle;
+                }
+            }
+        })
+        .collect()
+}
+
+fn evaluate(coefficients: &[f64], x: f64) -> f64 {
+    coefficients.iter().fold(0.0, |sum, c| sum * x + c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_roots(actual: Vec<f64>, expected: &[f64]) {
+        assert_eq!(actual.len(), expected.len(), "roots were {:?}", actual);
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-7, "roots were {:?}, expected {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn test_quadratic() {
+        assert_roots(solve_quadratic(1.0, -3.0, 2.0), &[1.0, 2.0]);
+        assert_roots(solve_quadratic(1.0, 0.0, 1.0), &[]);
+        assert_roots(solve_quadratic(0.0, 2.0, -4.0), &[2.0]);
+        assert_roots(solve_quadratic(1.0, 1e8, 1.0), &[-1e8, -1e-8]);
+    }
+
+    #[test]
+    fn test_cubic() {
+        assert_roots(solve_cubic(1.0, -6.0, 11.0, -6.0), &[1.0, 2.0, 3.0]);
+        assert_roots(solve_cubic(2.0, 0.0, 0.0, -16.0), &[2.0]);
+        assert_roots(solve_cubic(1.0, -3.0, 3.0, -1.0), &[1.0, 1.0]);
+    }
+
+    #[test]
+    fn test_quartic() {
+        // (x - 1)(x - 2)(x - 3)(x - 4)
+        assert_roots(solve_quartic(1.0, -10.0, 35.0, -50.0, 24.0), &[1.0, 2.0, 3.0, 4.0]);
+        // (x^2 - 4)(x^2 + 1)
+        assert_roots(solve_quartic(1.0, 0.0, -3.0, 0.0, -4.0), &[-2.0, 2.0]);
+        // x^4 + 1 has no real roots.
+        assert_roots(solve_quartic(1.0, 0.0, 0.0, 0.0, 1.0), &[]);
+        // Widely spread roots: (x - 0.001)(x - 1)(x - 10)(x - 1000)
+        let coeffs = [1.0, -1011.001, 11011.011, -10011.01, 10.0];
+        assert_roots(solve_quartic(coeffs[0], coeffs[1], coeffs[2], coeffs[3], coeffs[4]), &[0.001, 1.0, 10.0, 1000.0]);
+    }
+
+    #[test]
+    fn test_polynomial_in_interval() {
+        // (x - 1)(x - 2)(x - 3)(x - 4)(x - 5)
+        let coeffs = [1.0, -15.0, 85.0, -225.0, 274.0, -120.0];
+        assert_roots(solve_polynomial_in(&coeffs, 0.0, 6.0), &[1.0, 2.0, 3.0, 4.0, 5.0]);
+        assert_roots(solve_polynomial_in(&coeffs, 1.5, 3.5), &[2.0, 3.0]);
+        // x^2 - 1e-12 dips below zero only within 1e-6 of its turning point.
+        assert_roots(solve_polynomial_in(&[1.0, 0.0, -1e-12], -1.0, 1.0), &[-1e-6, 1e-6]);
+        assert_roots(solve_polynomial_in(&[1.0, 0.0, 1e-12], -1.0, 1.0), &[]);
+        assert_roots(solve_polynomial_in(&[3.0], -1.0, 1.0), &[]);
+    }
+}
@@ -42,10 +42,12 @@ impl Ray {
     }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Intersection {
     t_value: f64,
     object: Object,
+    u: f64,
+    v: f64,
 }
 
 impl Intersection {
@@ -53,6 +55,18 @@ impl Intersection {
         Self {
             t_value,
             object,
+            u: 0.0,
+            v: 0.0,
+        }
+    }
+
+    // Records where on a triangle the hit landed, for interpolating smooth triangle normals.
+    pub fn new_with_uv(t_value: f64, object: Object, u: f64, v: f64) -> Self {
+        Self {
+            t_value,
+            object,
+            u,
+            v,
         }
     }
 
@@ -60,8 +74,20 @@ impl Intersection {
         self.t_value
     }
 
-    pub fn get_object(&self) -> Object {
-        self.object
+    pub fn get_object(&self) -> &Object {
+        &self.object
+    }
+
+    pub fn mut_object(&mut self) -> &mut Object {
+        &mut self.object
+    }
+
+    pub fn get_u(&self) -> f64 {
+        self.u
+    }
+
+    pub fn get_v(&self) -> f64 {
+        self.v
     }
 }
 
@@ -1,3 +1,4 @@
+use rand::Rng;
 use rayon::prelude::*;
 use crate::prelude::*;
 
@@ -12,6 +13,55 @@ pub struct Camera {
     pixel_size: f64,
     half_width: f64,
     half_height: f64,
+    samples: usize,
+    sampling: SamplingStrategy,
+    adaptive_threshold: f64,
+    adaptive_depth: usize,
+    aperture: f64,
+    focal_distance: f64,
+    projection: Projection,
+    view_width: f64,
+}
+
+// Equirectangular covers the full sphere of directions; fisheye maps angle from the view axis
+// linearly to distance from the image centre, with the field of view spanning the image circle.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Projection {
+    Perspective,
+    Orthographic,
+    Equirectangular,
+    Fisheye,
+}
+
+impl std::fmt::Display for Projection {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match *self {
+            Projection::Perspective => write!(f, "Perspective"),
+            Projection::Orthographic => write!(f, "Orthographic"),
+            Projection::Equirectangular => write!(f, "Equirectangular"),
+            Projection::Fisheye => write!(f, "Fisheye"),
+        }
+    }
+}
+
+// Adaptive sampling shoots one ray per pixel, then subdivides pixels that differ from their neighbours.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SamplingStrategy {
+    Grid,
+    Jittered,
+    Random,
+    Adaptive,
+}
+
+impl std::fmt::Display for SamplingStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match *self {
+            SamplingStrategy::Grid => write!(f, "Grid"),
+            SamplingStrategy::Jittered => write!(f, "Jittered"),
+            SamplingStrategy::Random => write!(f, "Random"),
+            SamplingStrategy::Adaptive => write!(f, "Adaptive"),
+        }
+    }
 }
 
 impl Camera {
@@ -30,6 +80,14 @@ impl Camera {
             pixel_size: 0.0,
             half_width: 0.0,
             half_height: 0.0,
+            samples: 1,
+            sampling: SamplingStrategy::Grid,
+            adaptive_threshold: 0.1,
+            adaptive_depth: 2,
+            aperture: 0.0,
+            focal_distance: 1.0,
+            projection: Projection::Perspective,
+            view_width: 2.0,
         };
         new.initialize();
         new.update_transformations();
@@ -48,6 +106,14 @@ impl Camera {
             pixel_size: 0.0,
             half_width: 0.0,
             half_height: 0.0,
+            samples: camera.samples,
+            sampling: camera.sampling,
+            adaptive_threshold: camera.adaptive_threshold,
+            adaptive_depth: camera.adaptive_depth,
+            aperture: camera.aperture,
+            focal_distance: camera.focal_distance,
+            projection: camera.projection,
+            view_width: camera.view_width,
         };
         new.initialize();
         new.update_transformations();
@@ -84,8 +150,91 @@ impl Camera {
         self
     }
 
+    pub fn get_samples(&self) -> usize {
+        self.samples
+    }
+
+    pub fn get_sampling(&self) -> SamplingStrategy {
+        self.sampling
+    }
+
+    // Grid and jittered sampling use the largest square number of samples that fits.
+    pub fn set_samples(&mut self, samples: usize) -> &mut Self {
+        self.samples = samples.max(1);
+        self
+    }
+
+    pub fn set_sampling(&mut self, sampling: SamplingStrategy) -> &mut Self {
+        self.sampling = sampling;
+        self
+    }
+
+    pub fn get_adaptive_threshold(&self) -> f64 {
+        self.adaptive_threshold
+    }
+
+    pub fn get_adaptive_depth(&self) -> usize {
+        self.adaptive_depth
+    }
+
+    // Largest per-channel difference between neighbouring pixels that is left unrefined.
+    pub fn set_adaptive_threshold(&mut self, threshold: f64) -> &mut Self {
+        self.adaptive_threshold = threshold;
+        self
+    }
+
+    // Number of times an edge pixel may be split into quadrants.
+    pub fn set_adaptive_depth(&mut self, depth: usize) -> &mut Self {
+        self.adaptive_depth = depth.max(1);
+        self
+    }
+
+    pub fn get_aperture(&self) -> f64 {
+        self.aperture
+    }
+
+    pub fn get_focal_distance(&self) -> f64 {
+        self.focal_distance
+    }
+
+    // Radius of the lens. Zero gives a pinhole camera with everything in focus.
+    pub fn set_aperture(&mut self, aperture: f64) -> &mut Self {
+        self.aperture = aperture.max(0.0);
+        self
+    }
+
+    // Distance from the camera to the plane that is in perfect focus.
+    pub fn set_focal_distance(&mut self, distance: f64) -> &mut Self {
+        self.focal_distance = distance.max(EPSILON);
+        self
+    }
+
+    pub fn get_projection(&self) -> Projection {
+        self.projection
+    }
+
+    pub fn get_view_width(&self) -> f64 {
+        self.view_width
+    }
+
+    pub fn set_projection(&mut self, projection: Projection) -> &mut Self {
+        self.projection = projection;
+        self.initialize();
+        self
+    }
+
+    // Width of the visible region, in world units, for orthographic projection.
+    pub fn set_view_width(&mut self, width: f64) -> &mut Self {
+        self.view_width = width.max(EPSILON);
+        self.initialize();
+        self
+    }
+
     fn initialize(&mut self) -> &mut Self {
-        let half_view = (self.field_of_view / 2.0).tan();
+        let half_view = match self.projection {
+            Projection::Orthographic => self.view_width / 2.0,
+            _ => (self.field_of_view / 2.0).tan(),
+        };
         let aspect = self.hsize as f64 / self.vsize as f64;
         if aspect >= 1.0 {
             self.half_width = half_view;
@@ -144,12 +293,33 @@ impl Camera {
         self.set_transform(transform);
     }
 
-    fn ray_for_pixel(&self, x: usize, y: usize) -> Ray {
-        let xoffset = (x as f64 + 0.5) * self.pixel_size;
-        let yoffset = (y as f64 + 0.5) * self.pixel_size;
+    pub fn ray_for_pixel(&self, x: usize, y: usize) -> Ray {
+        self.ray_for_position(x as f64 + 0.5, y as f64 + 0.5)
+    }
+
+    // Ray through a position on the image plane, measured in pixels from the top-left corner.
+    fn ray_for_position(&self, px: f64, py: f64) -> Ray {
+        match self.projection {
+            Projection::Equirectangular => return self.ray_from_eye(self.equirectangular_direction(px, py)),
+            Projection::Fisheye => return self.ray_from_eye(self.fisheye_direction(px, py)),
+            _ => (),
+        }
+
+        let xoffset = px * self.pixel_size;
+        let yoffset = py * self.pixel_size;
         let world_x = self.half_width - xoffset;
         let world_y = self.half_height - yoffset;
 
+        if self.projection == Projection::Orthographic {
+            let origin = self.inverse_transform * point(world_x, world_y, 0.0);
+            let direction = (self.inverse_transform * vector(0.0, 0.0, -1.0)).normalize();
+            return Ray::new(origin, direction);
+        }
+
+        if self.aperture > 0.0 {
+            return self.thin_lens_ray(world_x, world_y);
+        }
+
         let pixel = self.inverse_transform * point(world_x, world_y, -1.0);
         let origin = self.inverse_transform * origin();
         let direction = (pixel - origin).normalize();
@@ -157,15 +327,186 @@ impl Camera {
         Ray::new(origin, direction)
     }
 
+    // Ray from the eye along a direction given in camera space.
+    fn ray_from_eye(&self, direction: Tuple) -> Ray {
+        let origin = self.inverse_transform * origin();
+        let direction = (self.inverse_transform * direction).normalize();
+        Ray::new(origin, direction)
+    }
+
+    // Longitude runs across the image and latitude down it, with the image centre looking down -z.
+    fn equirectangular_direction(&self, px: f64, py: f64) -> Tuple {
+        let longitude = (px / self.hsize as f64 - 0.5) * 2.0 * PI;
+        let latitude = (0.5 - py / self.vsize as f64) * PI;
+        vector(
+            -latitude.cos() * longitude.sin(),
+            latitude.sin(),
+            -latitude.cos() * longitude.cos()
+        )
+    }
+
+    // Offset from the image centre in units of the image circle's radius, with y pointing up.
+    fn fisheye_offset(&self, px: f64, py: f64) -> (f64, f64) {
+        let radius = self.hsize.min(self.vsize) as f64 / 2.0;
+        ((px - self.hsize as f64 / 2.0) / radius, (self.vsize as f64 / 2.0 - py) / radius)
+    }
+
+    fn fisheye_direction(&self, px: f64, py: f64) -> Tuple {
+        let (u, v) = self.fisheye_offset(px, py);
+        let r = (u * u + v * v).sqrt();
+        if r < EPSILON {
+            return vector(0.0, 0.0, -1.0);
+        }
+        let theta = r * self.field_of_view / 2.0;
+        vector(-theta.sin() * u / r, theta.sin() * v / r, -theta.cos())
+    }
+
+    // Whether a position on the image sees the scene at all; fisheye images are black outside their circle.
+    fn covers(&self, px: f64, py: f64) -> bool {
+        if self.projection != Projection::Fisheye {
+            return true;
+        }
+        let (u, v) = self.fisheye_offset(px, py);
+        u * u + v * v <= 1.0
+    }
+
+    fn color_at_position(&self, world: &World, px: f64, py: f64, remaining: usize) -> Color {
+        if !self.covers(px, py) {
+            return black();
+        }
+        world.color_at(self.ray_for_position(px, py), remaining)
+    }
+
+    // Starts the ray at a random point on the lens and aims it at where the pinhole ray meets the focal plane.
+    fn thin_lens_ray(&self, world_x: f64, world_y: f64) -> Ray {
+        let mut rng = rand::thread_rng();
+        let radius = self.aperture * rng.gen::<f64>().sqrt();
+        let theta = 2.0 * PI * rng.gen::<f64>();
+
+        let f = self.focal_distance;
+        let focal_point = point(world_x * f, world_y * f, -f);
+        let lens_point = point(radius * theta.cos(), radius * theta.sin(), 0.0);
+
+        let origin = self.inverse_transform * lens_point;
+        let direction = ((self.inverse_transform * focal_point) - origin).normalize();
+
+        Ray::new(origin, direction)
+    }
+
+    // Offsets within a pixel, each in [0, 1), at which to sample the scene.
+    fn sample_offsets(&self) -> Vec<(f64, f64)> {
+        let mut rng = rand::thread_rng();
+        let side = (self.samples as f64).sqrt().floor().max(1.0) as usize;
+        let step = 1.0 / side as f64;
+        let mut offsets = vec![];
+        match self.sampling {
+            SamplingStrategy::Grid => {
+                for j in 0..side {
+                    for i in 0..side {
+                        offsets.push(((i as f64 + 0.5) * step, (j as f64 + 0.5) * step));
+                    }
+                }
+            },
+            SamplingStrategy::Jittered => {
+                for j in 0..side {
+                    for i in 0..side {
+                        offsets.push(((i as f64 + rng.gen::<f64>()) * step, (j as f64 + rng.gen::<f64>()) * step));
+                    }
+                }
+            },
+            SamplingStrategy::Random => {
+                for _ in 0..self.samples {
+                    offsets.push((rng.gen::<f64>(), rng.gen::<f64>()));
+                }
+            },
+            SamplingStrategy::Adaptive => offsets.push((0.5, 0.5)),
+        }
+        offsets
+    }
+
+    fn color_for_pixel(&self, world: &World, x: usize, y: usize, remaining: usize) -> Color {
+        let offsets = self.sample_offsets();
+        let mut clr = black();
+        for (dx, dy) in &offsets {
+            clr += self.color_at_position(world, x as f64 + dx, y as f64 + dy, remaining);
+        }
+        clr * (1.0 / offsets.len() as f64)
+    }
+
+    fn is_edge_pixel(&self, base: &[Color], x: usize, y: usize) -> bool {
+        let clr = base[y * self.hsize + x];
+        let mut neighbours = vec![];
+        if x > 0 {
+            neighbours.push(base[y * self.hsize + x - 1]);
+        }
+        if x + 1 < self.hsize {
+            neighbours.push(base[y * self.hsize + x + 1]);
+        }
+        if y > 0 {
+            neighbours.push(base[(y - 1) * self.hsize + x]);
+        }
+        if y + 1 < self.vsize {
+            neighbours.push(base[(y + 1) * self.hsize + x]);
+        }
+        neighbours.iter().any(|n| color_difference(clr, *n) > self.adaptive_threshold)
+    }
+
+    // Samples the centres of the four quadrants of a square, recursing into them while they disagree.
+    fn refine(&self, world: &World, px: f64, py: f64, size: f64, depth: usize, remaining: usize) -> Color {
+        let half = size / 2.0;
+        let quadrants = [(px, py), (px + half, py), (px, py + half), (px + half, py + half)];
+        let colors: Vec<Color> = quadrants
+            .iter()
+            .map(|(qx, qy)| self.color_at_position(world, qx + half / 2.0, qy + half / 2.0, remaining))
+            .collect();
+        let differs = colors.iter().any(|c| color_difference(*c, colors[0]) > self.adaptive_threshold);
+
+        let mut clr = black();
+        for (i, (qx, qy)) in quadrants.iter().enumerate() {
+            if differs && depth > 1 {
+                clr += self.refine(world, *qx, *qy, half, depth - 1, remaining);
+            } else {
+                clr += colors[i];
+            }
+        }
+        clr * 0.25
+    }
+
+    // Second pass of adaptive sampling over one row of the image.
+    fn refine_row(&self, world: &World, base: &[Color], y: usize, row: &mut [Color], remaining: usize) {
+        for (x, pixel) in row.iter_mut().enumerate() {
+            if self.is_edge_pixel(base, x, y) {
+                *pixel = self.refine(world, x as f64, y as f64, 1.0, self.adaptive_depth, remaining);
+            }
+        }
+    }
+
+    fn parallel_refine(&self, world: &World, image: &mut Canvas, remaining: usize) {
+        if self.sampling != SamplingStrategy::Adaptive {
+            return;
+        }
+        let base = image.pixels().clone();
+        image
+            .pixels()
+            .par_chunks_mut(self.hsize)
+            .enumerate()
+            .for_each(|(y, row)| self.refine_row(world, &base, y, row, remaining));
+    }
+
     pub fn render(&self, world: &World) -> Canvas {
         let mut image = Canvas::new(self.hsize, self.vsize);
         for y in 0..self.vsize {
             for x in 0..self.hsize {
-                let ray = self.ray_for_pixel(x, y);
-                let color = world.color_at(ray, DEFAULT_RECURSION_DEPTH);
+                let color = self.color_for_pixel(world, x, y, DEFAULT_RECURSION_DEPTH);
                 image.write_pixel(x, y, color);
             }
         }
+        if self.sampling == SamplingStrategy::Adaptive {
+            let base = image.pixels().clone();
+            for (y, row) in image.pixels().chunks_mut(self.hsize).enumerate() {
+                self.refine_row(world, &base, y, row, DEFAULT_RECURSION_DEPTH);
+            }
+        }
         image
     }
 
@@ -185,10 +526,9 @@ impl Camera {
             .for_each(|(i, band)| {
                 for row in 0..BAND_SIZE {
                     for col in 0..self.hsize {
-                        let ray = self.ray_for_pixel(col, row + i * BAND_SIZE);
                         if (row * self.hsize) + col < band.len() {
                             band[(row * self.hsize) + col] =
-                                world.color_at(ray, DEFAULT_RECURSION_DEPTH);
+                                self.color_for_pixel(&world, col, row + i * BAND_SIZE, DEFAULT_RECURSION_DEPTH);
                             tracker.fetch_add(1, Ordering::SeqCst);
                         }
                     }
@@ -197,6 +537,7 @@ impl Camera {
                                ((self.hsize * self.vsize) as f64));
                 }
             });
+        self.parallel_refine(&world, &mut image, DEFAULT_RECURSION_DEPTH);
         image
     }
 
@@ -210,18 +551,25 @@ impl Camera {
             .for_each(|(i, band)| {
                 for row in 0..BAND_SIZE {
                     for col in 0..self.hsize {
-                        let ray = self.ray_for_pixel(col, row + i * BAND_SIZE);
                         if (row * self.hsize) + col < band.len() {
                             band[(row * self.hsize) + col] =
-                                world.color_at(ray, 2);
+                                self.color_for_pixel(&world, col, row + i * BAND_SIZE, 2);
                         }
                     }
                 }
             });
+        self.parallel_refine(&world, &mut image, 2);
         image
     }
 }
 
+fn color_difference(a: Color, b: Color) -> f64 {
+    let diff = a - b;
+    diff.get_red().abs()
+        .max(diff.get_green().abs())
+        .max(diff.get_blue().abs())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -271,6 +619,231 @@ mod tests {
         assert_eq!(r.get_direction(), vector(FRAC_1_SQRT_2, 0.0, -FRAC_1_SQRT_2));
     }
 
+    #[test]
+    fn test_grid_sample_offsets() {
+        let mut c = Camera::new(10, 10, FRAC_PI_2);
+        assert_eq!(c.sample_offsets(), vec![(0.5, 0.5)]);
+
+        c.set_samples(4);
+        assert_eq!(c.sample_offsets(), vec![(0.25, 0.25), (0.75, 0.25), (0.25, 0.75), (0.75, 0.75)]);
+
+        c.set_samples(5);
+        assert_eq!(c.sample_offsets().len(), 4);
+    }
+
+    #[test]
+    fn test_jittered_sample_offsets() {
+        let mut c = Camera::new(10, 10, FRAC_PI_2);
+        c.set_samples(9).set_sampling(SamplingStrategy::Jittered);
+        let offsets = c.sample_offsets();
+        assert_eq!(offsets.len(), 9);
+        for (i, (dx, dy)) in offsets.iter().enumerate() {
+            let cell_x = (i % 3) as f64 / 3.0;
+            let cell_y = (i / 3) as f64 / 3.0;
+            assert!(*dx >= cell_x && *dx < cell_x + 1.0 / 3.0);
+            assert!(*dy >= cell_y && *dy < cell_y + 1.0 / 3.0);
+        }
+    }
+
+    #[test]
+    fn test_random_sample_offsets() {
+        let mut c = Camera::new(10, 10, FRAC_PI_2);
+        c.set_samples(7).set_sampling(SamplingStrategy::Random);
+        let offsets = c.sample_offsets();
+        assert_eq!(offsets.len(), 7);
+        for (dx, dy) in offsets {
+            assert!((0.0..1.0).contains(&dx));
+            assert!((0.0..1.0).contains(&dy));
+        }
+    }
+
+    #[test]
+    fn test_supersampling_averages_pixel() {
+        let mut sphere = spheres::new();
+        sphere.set_transform(scaling(2.0, 2.0, 2.0));
+        sphere.set_ambient(1.0).set_diffuse(0.0).set_specular(0.0);
+        let w = World::new_from_raw(vec![ObjectHolder::from_object(sphere)], vec![]);
+
+        let mut c = Camera::new(1, 1, FRAC_PI_2);
+        c.set_transform(view_transform(point(0.0, 0.0, -5.0), origin(), vector(0.0, 1.0, 0.0)));
+        assert_eq!(c.render(&w).pixel_at(0, 0), white());
+
+        c.set_samples(16);
+        assert_eq!(c.render(&w).pixel_at(0, 0), color(0.25, 0.25, 0.25));
+        assert_eq!(c.parallel_render(w.clone(), Arc::new(AtomicUsize::new(0))).pixel_at(0, 0), color(0.25, 0.25, 0.25));
+        assert_eq!(c.preview_parallel_render(w).pixel_at(0, 0), color(0.25, 0.25, 0.25));
+    }
+
+    #[test]
+    fn test_adaptive_edge_detection() {
+        let mut c = Camera::new(3, 3, FRAC_PI_2);
+        c.set_sampling(SamplingStrategy::Adaptive);
+        let mut base = vec![black(); 9];
+        base[2] = white();
+        assert!(!c.is_edge_pixel(&base, 0, 0));
+        assert!(!c.is_edge_pixel(&base, 0, 2));
+        assert!(c.is_edge_pixel(&base, 1, 0));
+        assert!(c.is_edge_pixel(&base, 2, 0));
+        assert!(c.is_edge_pixel(&base, 2, 1));
+
+        base[2] = color(0.05, 0.05, 0.05);
+        assert!(!c.is_edge_pixel(&base, 2, 0));
+    }
+
+    #[test]
+    fn test_adaptive_refines_only_edges() {
+        let mut sphere = spheres::new();
+        sphere.set_ambient(1.0).set_diffuse(0.0).set_specular(0.0);
+        let w = World::new_from_raw(vec![ObjectHolder::from_object(sphere)], vec![]);
+
+        let mut c = Camera::new(9, 9, FRAC_PI_4);
+        c.set_transform(view_transform(point(0.0, 0.0, -5.0), origin(), vector(0.0, 1.0, 0.0)));
+
+        let blended_pixels = |image: &Canvas| {
+            let mut blended = 0;
+            for y in 0..9 {
+                for x in 0..9 {
+                    let red = image.pixel_at(x, y).get_red();
+                    if red > EPSILON && red < 1.0 - EPSILON {
+                        blended += 1;
+                    }
+                }
+            }
+            blended
+        };
+
+        assert_eq!(blended_pixels(&c.render(&w)), 0);
+
+        c.set_sampling(SamplingStrategy::Adaptive).set_adaptive_depth(3);
+        let images = [c.render(&w), c.parallel_render(w.clone(), Arc::new(AtomicUsize::new(0)))];
+        for image in images.iter() {
+            assert_eq!(image.pixel_at(4, 4), white());
+            assert_eq!(image.pixel_at(0, 0), black());
+            assert!(blended_pixels(image) > 0);
+        }
+    }
+
+    #[test]
+    fn test_zero_aperture_is_pinhole() {
+        let mut c = Camera::new(201, 101, FRAC_PI_2);
+        c.set_focal_distance(4.0);
+        let r = c.ray_for_pixel(0, 0);
+        assert_eq!(r.get_origin(), origin());
+        assert_eq!(r.get_direction(), vector(0.66519, 0.33259, -0.66851));
+    }
+
+    #[test]
+    fn test_thin_lens_rays_converge_on_focal_plane() {
+        let mut c = Camera::new(201, 101, FRAC_PI_2);
+        c.set_transform(view_transform(point(0.0, 0.0, -5.0), origin(), vector(0.0, 1.0, 0.0)));
+        c.set_aperture(0.5).set_focal_distance(5.0);
+
+        for _ in 0..20 {
+            let r = c.ray_for_pixel(100, 50);
+            let offset = r.get_origin() - point(0.0, 0.0, -5.0);
+            assert!(offset.z.approx_eq(0.0));
+            assert!(offset.magnitude() <= 0.5 + EPSILON);
+
+            let t = 5.0 / r.get_direction().z;
+            assert_eq!(r.position(t), origin());
+        }
+
+        let r = c.ray_for_pixel(0, 0);
+        let t = 5.0 / r.get_direction().z;
+        let pinhole = Camera::new(201, 101, FRAC_PI_2);
+        let direction = pinhole.ray_for_pixel(0, 0).get_direction();
+        let expected = direction * (5.0 / -direction.z);
+        assert_eq!(r.position(t), point(-expected.x, expected.y, 0.0));
+    }
+
+    #[test]
+    fn test_orthographic_pixel_size() {
+        let mut c = Camera::new(200, 100, FRAC_PI_2);
+        c.set_projection(Projection::Orthographic).set_view_width(4.0);
+        assert!(c.pixel_size.approx_eq(0.02));
+        assert!(c.half_height.approx_eq(1.0));
+
+        c.set_projection(Projection::Perspective);
+        assert!(c.pixel_size.approx_eq(0.01));
+    }
+
+    #[test]
+    fn test_orthographic_rays_are_parallel() {
+        let mut c = Camera::new(201, 101, FRAC_PI_2);
+        c.set_projection(Projection::Orthographic).set_view_width(2.01);
+
+        let r = c.ray_for_pixel(100, 50);
+        assert_eq!(r.get_origin(), origin());
+        assert_eq!(r.get_direction(), vector(0.0, 0.0, -1.0));
+
+        let r = c.ray_for_pixel(0, 0);
+        assert_eq!(r.get_origin(), point(1.0, 0.5, 0.0));
+        assert_eq!(r.get_direction(), vector(0.0, 0.0, -1.0));
+    }
+
+    #[test]
+    fn test_orthographic_ray_transformed() {
+        let mut c = Camera::new(201, 101, FRAC_PI_2);
+        c.set_projection(Projection::Orthographic).set_view_width(2.01);
+        c.set_transform(view_transform(point(0.0, 0.0, -5.0), origin(), vector(0.0, 1.0, 0.0)));
+
+        let r = c.ray_for_pixel(0, 0);
+        assert_eq!(r.get_origin(), point(-1.0, 0.5, -5.0));
+        assert_eq!(r.get_direction(), vector(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn test_equirectangular_directions() {
+        let mut c = Camera::new(200, 100, FRAC_PI_2);
+        c.set_projection(Projection::Equirectangular);
+
+        let r = c.ray_for_position(100.0, 50.0);
+        assert_eq!(r.get_origin(), origin());
+        assert_eq!(r.get_direction(), vector(0.0, 0.0, -1.0));
+
+        assert_eq!(c.ray_for_position(150.0, 50.0).get_direction(), vector(-1.0, 0.0, 0.0));
+        assert_eq!(c.ray_for_position(50.0, 50.0).get_direction(), vector(1.0, 0.0, 0.0));
+        assert_eq!(c.ray_for_position(0.0, 50.0).get_direction(), vector(0.0, 0.0, 1.0));
+        assert_eq!(c.ray_for_position(100.0, 0.0).get_direction(), vector(0.0, 1.0, 0.0));
+        assert_eq!(c.ray_for_position(100.0, 100.0).get_direction(), vector(0.0, -1.0, 0.0));
+    }
+
+    #[test]
+    fn test_equirectangular_ray_transformed() {
+        let mut c = Camera::new(200, 100, FRAC_PI_2);
+        c.set_projection(Projection::Equirectangular);
+        c.set_transform(view_transform(point(0.0, 0.0, -5.0), origin(), vector(0.0, 1.0, 0.0)));
+
+        let r = c.ray_for_position(100.0, 50.0);
+        assert_eq!(r.get_origin(), point(0.0, 0.0, -5.0));
+        assert_eq!(r.get_direction(), vector(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn test_fisheye_directions() {
+        let mut c = Camera::new(100, 100, PI);
+        c.set_projection(Projection::Fisheye);
+
+        assert_eq!(c.ray_for_position(50.0, 50.0).get_direction(), vector(0.0, 0.0, -1.0));
+        assert_eq!(c.ray_for_position(100.0, 50.0).get_direction(), vector(-1.0, 0.0, 0.0));
+        assert_eq!(c.ray_for_position(50.0, 0.0).get_direction(), vector(0.0, 1.0, 0.0));
+
+        let r = c.ray_for_position(75.0, 50.0);
+        assert_eq!(r.get_direction(), vector(-FRAC_1_SQRT_2, 0.0, -FRAC_1_SQRT_2));
+    }
+
+    #[test]
+    fn test_fisheye_black_outside_circle() {
+        let mut c = Camera::new(100, 100, PI);
+        c.set_projection(Projection::Fisheye);
+        assert!(c.covers(50.0, 50.0));
+        assert!(c.covers(50.0, 0.0));
+        assert!(!c.covers(0.5, 0.5));
+
+        let w = World::new_default();
+        assert_eq!(c.color_at_position(&w, 0.5, 0.5, 5), black());
+    }
+
     #[test]
     fn test_render_world() {
         let w = World::new_default();
@@ -1,15 +1,44 @@
+use rand::Rng;
 use crate::prelude::*;
 
 #[derive(Copy, Clone, Debug)]
 pub struct Light {
     position: Tuple,
     intensity: Color,
-    light: LightType
+    light: LightType,
+    attenuation: Attenuation,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum LightType {
     PointLight,
+    DirectionalLight {direction: Tuple},
+    SpotLight {direction: Tuple, inner_angle: f64, outer_angle: f64},
+    AreaLight {corner: Tuple, uvec: Tuple, usteps: usize, vvec: Tuple, vsteps: usize, jitter: bool},
+}
+
+// Falloff of a light's intensity with distance. Directional lights never attenuate.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Attenuation {
+    None,
+    Linear,
+    InverseSquare,
+    Coefficients {constant: f64, linear: f64, quadratic: f64},
+}
+
+impl Attenuation {
+    // The denominator is kept above EPSILON so a point sitting on the light doesn't get an infinite factor.
+    pub fn factor(&self, distance: f64) -> f64 {
+        let denominator = match *self {
+            Attenuation::None => 1.0,
+            Attenuation::Linear => distance,
+            Attenuation::InverseSquare => distance.powi(2),
+            Attenuation::Coefficients {constant, linear, quadratic} => {
+                constant + linear * distance + quadratic * distance.powi(2)
+            },
+        };
+        1.0 / denominator.max(EPSILON)
+    }
 }
 
 impl Light {
@@ -17,11 +46,56 @@ impl Light {
         Self {
             position,
             intensity,
-            light: LightType::PointLight
+            light: LightType::PointLight,
+            attenuation: Attenuation::None,
+        }
+    }
+
+    pub fn new_directional(direction: Tuple, intensity: Color) -> Self {
+        Self {
+            position: origin(),
+            intensity,
+            light: LightType::DirectionalLight {direction: direction.normalize()},
+            attenuation: Attenuation::None,
+        }
+    }
+
+    // Angles are measured from the aim direction to the edge of the cone, in radians.
+    // Intensity is full inside inner_angle and fades smoothly to zero at outer_angle.
+    pub fn new_spot(position: Tuple, direction: Tuple, inner_angle: f64, outer_angle: f64, intensity: Color) -> Self {
+        Self {
+            position,
+            intensity,
+            light: LightType::SpotLight {direction: direction.normalize(), inner_angle, outer_angle},
+            attenuation: Attenuation::None,
+        }
+    }
+
+    // A rectangle spanned by uvec and vvec from corner, sampled on a usteps x vsteps grid.
+    pub fn new_area(corner: Tuple, uvec: Tuple, usteps: usize, vvec: Tuple, vsteps: usize, intensity: Color) -> Self {
+        Self {
+            position: corner + uvec * 0.5 + vvec * 0.5,
+            intensity,
+            light: LightType::AreaLight {
+                corner,
+                uvec,
+                usteps: usteps.max(1),
+                vvec,
+                vsteps: vsteps.max(1),
+                jitter: false,
+            },
+            attenuation: Attenuation::None,
         }
     }
 
+    pub fn get_light_type(&self) -> LightType {
+        self.light
+    }
+
     pub fn set_position(&mut self, position: Tuple) -> &mut Self {
+        if let LightType::AreaLight {ref mut corner, uvec: _, usteps: _, vvec: _, vsteps: _, jitter: _} = self.light {
+            *corner = *corner + (position - self.position);
+        }
         self.position = position;
         self
     }
@@ -38,43 +112,161 @@ impl Light {
     pub fn get_intensity(&self) -> Color {
         self.intensity
     }
-}
 
-pub fn lighting(material: Material, object: Object, light: Light, point: Tuple, eyev: Tuple, normalv: Tuple, in_shadow: bool) -> Color {
+    pub fn set_direction(&mut self, direction: Tuple) -> &mut Self {
+        match self.light {
+            LightType::DirectionalLight {direction: ref mut d} => *d = direction.normalize(),
+            LightType::SpotLight {direction: ref mut d, inner_angle: _, outer_angle: _} => *d = direction.normalize(),
+            _ => (),
+        }
+        self
+    }
+
+    pub fn set_cone_angles(&mut self, inner: f64, outer: f64) -> &mut Self {
+        if let LightType::SpotLight {direction: _, ref mut inner_angle, ref mut outer_angle} = self.light {
+            *inner_angle = inner;
+            *outer_angle = outer;
+        }
+        self
+    }
+
+    pub fn get_attenuation(&self) -> Attenuation {
+        self.attenuation
+    }
+
+    pub fn set_attenuation(&mut self, attenuation: Attenuation) -> &mut Self {
+        self.attenuation = attenuation;
+        self
+    }
+
+    pub fn set_jitter(&mut self, jitter: bool) -> &mut Self {
+        if let LightType::AreaLight {corner: _, uvec: _, usteps: _, vvec: _, vsteps: _, jitter: ref mut j} = self.light {
+            *j = jitter;
+        }
+        self
+    }
+
+    // Intensity of the light as it arrives at the given point.
+    pub fn intensity_at(&self, point: Tuple) -> Color {
+        let falloff = match self.light {
+            LightType::DirectionalLight {direction: _} => return self.intensity,
+            _ => self.attenuation.factor(self.distance_from(point)),
+        };
+        match self.light {
+            LightType::SpotLight {direction, inner_angle, outer_angle} => {
+                let cos_angle = -self.direction_from(point) * direction;
+                self.intensity * falloff * smoothstep(outer_angle.cos(), inner_angle.cos(), cos_angle)
+            },
+            _ => self.intensity * falloff,
+        }
+    }
 
-    let clr = object.pattern_at_object(point);
+    // Unit vector pointing from the given point towards the light.
+    pub fn direction_from(&self, point: Tuple) -> Tuple {
+        match self.light {
+            LightType::DirectionalLight {direction} => -direction,
+            _ => (self.position - point).normalize(),
+        }
+    }
 
-    let effective_color = clr * light.get_intensity();
+    pub fn distance_from(&self, point: Tuple) -> f64 {
+        match self.light {
+            LightType::DirectionalLight {direction: _} => f64::INFINITY,
+            _ => (self.position - point).magnitude(),
+        }
+    }
 
-    let lightv = (light.get_position() - point).normalize();
+    // Points on the light's surface used for shading and shadows. Positional lights have one.
+    pub fn sample_points(&self) -> Vec<Tuple> {
+        match self.light {
+            LightType::AreaLight {corner, uvec, usteps, vvec, vsteps, jitter} => {
+                let mut rng = rand::thread_rng();
+                let cell_u = uvec / usteps as f64;
+                let cell_v = vvec / vsteps as f64;
+                let mut points = Vec::with_capacity(usteps * vsteps);
+                for v in 0..vsteps {
+                    for u in 0..usteps {
+                        let (ju, jv) = if jitter {
+                            (rng.gen::<f64>(), rng.gen::<f64>())
+                        } else {
+                            (0.5, 0.5)
+                        };
+                        points.push(corner + cell_u * (u as f64 + ju) + cell_v * (v as f64 + jv));
+                    }
+                }
+                points
+            },
+            _ => vec![self.position],
+        }
+    }
 
-    let ambient = effective_color * material.get_ambient();
+    // Unit direction towards, and distance to, each sample of the light as seen from the given point.
+    pub fn samples_from(&self, point: Tuple) -> Vec<(Tuple, f64)> {
+        match self.light {
+            LightType::DirectionalLight {direction} => vec![(-direction, f64::INFINITY)],
+            _ => self.sample_points()
+                .into_iter()
+                .map(|sample| {
+                    let v = sample - point;
+                    (v.normalize(), v.magnitude())
+                })
+                .collect(),
+        }
+    }
+}
 
-    if in_shadow {
-        return ambient;
+fn smoothstep(edge0: f64, edge1: f64, x: f64) -> f64 {
+    if edge1 - edge0 < EPSILON {
+        return if x >= edge1 { 1.0 } else { 0.0 };
     }
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+// light_visibility is the fraction of the light that reaches the point, from 0.0 (fully shadowed) to 1.0.
+pub fn lighting(material: &Material, object: &Object, light: Light, point: Tuple, eyev: Tuple, normalv: Tuple, light_visibility: f64) -> Color {
+    let samples = light.samples_from(point);
+    let weight = light_visibility / samples.len() as f64;
+    let lit: Vec<(Tuple, f64)> = samples.into_iter().map(|(lightv, _)| (lightv, weight)).collect();
+    lighting_color(material, object.pattern_at_object(point), light, point, eyev, normalv, &lit)
+}
+
+// Lighting for a surface whose colour at the point has already been looked up, e.g. from a texture.
+// Each sample is a direction towards the light and the share of its intensity arriving from there, so
+// the samples used for the shadow test are the same ones shaded; hidden samples can be left out.
+pub fn lighting_color(material: &Material, clr: Color, light: Light, point: Tuple, eyev: Tuple, normalv: Tuple, samples: &[(Tuple, f64)]) -> Color {
+
+    let intensity = light.intensity_at(point);
+
+    let effective_color = clr * intensity;
 
-    let mut diffuse = black();
-    let mut specular = black();
+    // Ambient light stands in for light scattered around the scene, so neither spot cones nor distance
+    // falloff dim it.
+    let ambient = clr * light.get_intensity() * material.get_ambient();
 
-    let light_dot_normal = lightv * normalv;
+    let mut sum = black();
 
-    if light_dot_normal >= 0.0 {
-        diffuse = effective_color * material.get_diffuse() * light_dot_normal;
+    for &(lightv, weight) in samples {
+        if weight <= 0.0 {
+            continue;
+        }
+        let light_dot_normal = lightv * normalv;
+
+        if light_dot_normal >= 0.0 {
+            sum += effective_color * material.get_diffuse() * light_dot_normal * weight;
 
-        let reflectv = -lightv
-            .reflect_vector(normalv);
+            let reflectv = -lightv
+                .reflect_vector(normalv);
 
-        let reflect_dot_eye = reflectv * eyev;
+            let reflect_dot_eye = reflectv * eyev;
 
-        if reflect_dot_eye <= 0.0 {
-            specular = color(0.0, 0.0, 0.0);
-        } else {
-            let factor = reflect_dot_eye.powf(material.get_shininess());
-            specular = light.get_intensity() * material.get_specular() * factor;
+            if reflect_dot_eye > 0.0 {
+                let factor = reflect_dot_eye.powf(material.get_shininess());
+                sum += intensity * material.get_specular() * factor * weight;
+            }
         }
     }
-    ambient + diffuse + specular
+    ambient + sum
 }
 
 #[cfg(test)]
@@ -91,30 +283,184 @@ mod tests {
         let normalv = vector(0.0, 0.0, -1.0);
         let light = Light::new(point(0.0, 0.0, -10.0),
                                     color(1.0, 1.0, 1.0));
-        let result = lighting(m, object, light, position, eyev, normalv, false);
+        let result = lighting(&m, &object, light, position, eyev, normalv, 1.0);
         assert_eq!(result, color(1.9, 1.9, 1.9));
 
         let eyev = vector(0.0, FRAC_1_SQRT_2, -FRAC_1_SQRT_2);
-        let result = lighting(m, object, light, position, eyev, normalv, false);
+        let result = lighting(&m, &object, light, position, eyev, normalv, 1.0);
         assert_eq!(result, color(1.0, 1.0, 1.0));
 
         let eyev = vector(0.0, 0.0, -1.0);
         let light = Light::new(point(0.0, 10.0, -10.0),
                                     color(1.0, 1.0, 1.0));
-        let result = lighting(m, object, light, position, eyev, normalv, false);
+        let result = lighting(&m, &object, light, position, eyev, normalv, 1.0);
         assert_eq!(result, color(0.7364, 0.7364, 0.7364));
 
         let eyev = vector(0.0, -FRAC_1_SQRT_2, -FRAC_1_SQRT_2);
-        let result = lighting(m, object, light, position, eyev, normalv, false);
+        let result = lighting(&m, &object, light, position, eyev, normalv, 1.0);
         assert_eq!(result, color(1.6364, 1.6364, 1.6364));
 
         let eyev = vector(0.0, 0.0, -1.0);
         let light = Light::new(point(0.0, 0.0, 10.0),
                                     color(1.0, 1.0, 1.0));
-        let result = lighting(m, object, light, position, eyev, normalv, false);
+        let result = lighting(&m, &object, light, position, eyev, normalv, 1.0);
         assert_eq!(result, color(0.1, 0.1, 0.1));
     }
 
+    #[test]
+    fn test_lighting_directional() {
+        let m = Material::new();
+        let object = spheres::new();
+        let eyev = vector(0.0, 0.0, -1.0);
+        let normalv = vector(0.0, 0.0, -1.0);
+
+        let light = Light::new_directional(vector(0.0, 0.0, 1.0), white());
+        let result = lighting(&m, &object, light, origin(), eyev, normalv, 1.0);
+        assert_eq!(result, color(1.9, 1.9, 1.9));
+
+        let result = lighting(&m, &object, light, point(100.0, -40.0, 0.0), eyev, normalv, 1.0);
+        assert_eq!(result, color(1.9, 1.9, 1.9));
+
+        let light = Light::new_directional(vector(0.0, -1.0, 1.0), white());
+        let result = lighting(&m, &object, light, origin(), eyev, normalv, 1.0);
+        assert_eq!(result, color(0.7364, 0.7364, 0.7364));
+    }
+
+    #[test]
+    fn test_directional_light_distance() {
+        let light = Light::new_directional(vector(0.0, -2.0, 0.0), white());
+        assert_eq!(light.direction_from(point(3.0, 0.0, -7.0)), vector(0.0, 1.0, 0.0));
+        assert_eq!(light.distance_from(point(3.0, 0.0, -7.0)), f64::INFINITY);
+    }
+
+    #[test]
+    fn test_spot_light_cone() {
+        let light = Light::new_spot(point(0.0, 10.0, 0.0), vector(0.0, -1.0, 0.0), PI / 8.0, PI / 4.0, white());
+
+        assert_eq!(light.intensity_at(origin()), white());
+        assert_eq!(light.intensity_at(point(10.0 * (PI / 8.0).tan(), 0.0, 0.0)), white());
+        assert_eq!(light.intensity_at(point(10.0, 0.0, 0.0)), black());
+        assert_eq!(light.intensity_at(point(0.0, 20.0, 0.0)), black());
+
+        let mid = 10.0 * (3.0 * PI / 16.0).tan();
+        let falloff = light.intensity_at(point(0.0, 0.0, mid)).get_red();
+        assert!(falloff > 0.0 && falloff < 1.0);
+    }
+
+    #[test]
+    fn test_lighting_spot_light() {
+        let m = Material::new();
+        let object = spheres::new();
+        let eyev = vector(0.0, 0.0, -1.0);
+        let normalv = vector(0.0, 0.0, -1.0);
+
+        let light = Light::new_spot(point(0.0, 0.0, -10.0), vector(0.0, 0.0, 1.0), PI / 8.0, PI / 4.0, white());
+        let result = lighting(&m, &object, light, origin(), eyev, normalv, 1.0);
+        assert_eq!(result, color(1.9, 1.9, 1.9));
+
+        // Outside the cone only the ambient term is left.
+        let mut light = light;
+        light.set_direction(vector(1.0, 0.0, 0.0));
+        let result = lighting(&m, &object, light, origin(), eyev, normalv, 1.0);
+        assert_eq!(result, color(0.1, 0.1, 0.1));
+    }
+
+    #[test]
+    fn test_area_light() {
+        let light = Light::new_area(origin(), vector(2.0, 0.0, 0.0), 4, vector(0.0, 0.0, 1.0), 2, white());
+        assert_eq!(light.get_position(), point(1.0, 0.0, 0.5));
+
+        let samples = light.sample_points();
+        assert_eq!(samples.len(), 8);
+        assert_eq!(samples[0], point(0.25, 0.0, 0.25));
+        assert_eq!(samples[1], point(0.75, 0.0, 0.25));
+        assert_eq!(samples[6], point(1.25, 0.0, 0.75));
+        assert_eq!(samples[7], point(1.75, 0.0, 0.75));
+    }
+
+    #[test]
+    fn test_area_light_jitter_stays_in_cells() {
+        let mut light = Light::new_area(origin(), vector(2.0, 0.0, 0.0), 4, vector(0.0, 0.0, 1.0), 2, white());
+        light.set_jitter(true);
+        for (i, sample) in light.sample_points().iter().enumerate() {
+            let u = (i % 4) as f64 * 0.5;
+            let v = (i / 4) as f64 * 0.5;
+            assert!(sample.x >= u && sample.x <= u + 0.5);
+            assert!(sample.z >= v && sample.z <= v + 0.5);
+        }
+    }
+
+    #[test]
+    fn test_lighting_area_light() {
+        let corner = point(-0.5, -0.5, -5.0);
+        let light = Light::new_area(corner, vector(1.0, 0.0, 0.0), 2, vector(0.0, 1.0, 0.0), 2, white());
+        let mut shape = spheres::new();
+        shape.set_ambient(0.1).set_diffuse(0.9).set_specular(0.0).set_color(white());
+        let eye = point(0.0, 0.0, -5.0);
+
+        let pt = point(0.0, 0.0, -1.0);
+        let eyev = (eye - pt).normalize();
+        let normalv = shape.normal_at(pt);
+        let result = lighting(shape.get_material(), &shape, light, pt, eyev, normalv, 1.0);
+        assert_eq!(result, color(0.9965, 0.9965, 0.9965));
+
+        let pt = point(0.0, FRAC_1_SQRT_2, -FRAC_1_SQRT_2);
+        let eyev = (eye - pt).normalize();
+        let normalv = shape.normal_at(pt);
+        let result = lighting(shape.get_material(), &shape, light, pt, eyev, normalv, 1.0);
+        assert_eq!(result, color(0.62318, 0.62318, 0.62318));
+    }
+
+    #[test]
+    fn test_attenuation() {
+        let mut light = Light::new(point(0.0, 0.0, -10.0), white());
+        assert_eq!(light.intensity_at(origin()), white());
+        assert_eq!(light.intensity_at(point(0.0, 0.0, 90.0)), white());
+
+        light.set_attenuation(Attenuation::Linear);
+        assert_eq!(light.intensity_at(origin()), color(0.1, 0.1, 0.1));
+
+        light.set_attenuation(Attenuation::InverseSquare);
+        assert_eq!(light.intensity_at(origin()), color(0.01, 0.01, 0.01));
+        assert_eq!(light.intensity_at(point(0.0, 0.0, -9.0)), white());
+
+        light.set_attenuation(Attenuation::Coefficients {constant: 1.0, linear: 0.5, quadratic: 0.01});
+        assert_eq!(light.intensity_at(origin()), color(0.14286, 0.14286, 0.14286));
+    }
+
+    #[test]
+    fn test_attenuation_at_the_light_is_finite() {
+        for attenuation in [
+            Attenuation::Linear,
+            Attenuation::InverseSquare,
+            Attenuation::Coefficients {constant: 0.0, linear: 1.0, quadratic: 0.0},
+        ] {
+            assert!(attenuation.factor(0.0).is_finite());
+        }
+        let mut light = Light::new(origin(), white());
+        light.set_attenuation(Attenuation::Linear);
+        assert!(light.intensity_at(origin()).get_red().is_finite());
+    }
+
+    #[test]
+    fn test_directional_light_ignores_attenuation() {
+        let mut light = Light::new_directional(vector(0.0, -1.0, 0.0), white());
+        light.set_attenuation(Attenuation::InverseSquare);
+        assert_eq!(light.intensity_at(point(0.0, -1000.0, 0.0)), white());
+    }
+
+    #[test]
+    fn test_lighting_with_attenuation() {
+        let m = Material::new();
+        let object = spheres::new();
+        let eyev = vector(0.0, 0.0, -1.0);
+        let normalv = vector(0.0, 0.0, -1.0);
+        let mut light = Light::new(point(0.0, 0.0, -2.0), white());
+        light.set_attenuation(Attenuation::InverseSquare);
+        let result = lighting(&m, &object, light, origin(), eyev, normalv, 1.0);
+        assert_eq!(result, color(0.55, 0.55, 0.55));
+    }
+
     #[test]
     fn test_shadow() {
         let m = Material::new();
@@ -124,7 +470,7 @@ mod tests {
         let normalv = vector(0.0, 0.0, -1.0);
         let light = Light::new(point(0.0, 0.0, -10.0), white());
 
-        let result = lighting(m, s, light, position, eyev, normalv, true);
+        let result = lighting(&m, &s, light, position, eyev, normalv, 0.0);
         assert_eq!(result, color(0.1, 0.1, 0.1));
     }
 
@@ -136,12 +482,12 @@ mod tests {
         m.set_ambient(1.0);
         m.set_pattern(stripe(white(), black()));
         let mut s = spheres::new();
-        s.set_material(m);
+        s.set_material(m.clone());
         let eyev = vector(0.0, 0.0, -1.0);
         let normalv = vector(0.0, 0.0, -1.0);
         let light = Light::new(point(0.0, 0.0, -10.0), white());
-        let c1 = lighting(m, s, light, point(0.9, 0.0, 0.0), eyev, normalv, false);
-        let c2 = lighting(m, s, light, point(1.1, 0.0, 0.0), eyev, normalv, false);
+        let c1 = lighting(&m, &s, light, point(0.9, 0.0, 0.0), eyev, normalv, 1.0);
+        let c2 = lighting(&m, &s, light, point(1.1, 0.0, 0.0), eyev, normalv, 1.0);
 
         assert_eq!(c1, white());
         assert_eq!(c2, black());
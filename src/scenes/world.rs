@@ -6,6 +6,7 @@ pub const DEFAULT_RECURSION_DEPTH: usize = 5;
 pub struct World {
     objects: Vec<Arc<RwLock<ObjectHolder>>>,
     lights: Vec<Arc<RwLock<Light>>>,
+    bvh: Option<Bvh>,
 }
 
 impl World {
@@ -13,6 +14,7 @@ impl World {
         Self {
             objects,
             lights,
+            bvh: None,
         }
     }
 
@@ -31,7 +33,8 @@ impl World {
                     v.push(Arc::new(RwLock::new(light)));
                 }
                 v
-            }
+            },
+            bvh: None,
         }
     }
 
@@ -65,7 +68,9 @@ impl World {
         World::new_from_raw(objects, lights)
     }
 
-    pub fn add_object_holder(&mut self, element: ObjectHolder) {
+    pub fn add_object_holder(&mut self, mut element: ObjectHolder) {
+        self.bvh = None;
+        element.set_parent_transform(Matrix4::identity());
         self.objects.push(
             Arc::new(
                 RwLock::new(
@@ -83,48 +88,77 @@ impl World {
         self.add_object_holder(ObjectHolder::from_group(group));
     }
 
+    pub fn add_csg(&mut self, csg: Csg) {
+        self.add_object_holder(ObjectHolder::from_csg(csg));
+    }
+
+    pub fn add_heightfield(&mut self, heightfield: Heightfield) {
+        self.add_object_holder(ObjectHolder::from_heightfield(heightfield));
+    }
+
+    pub fn add_instance(&mut self, instance: Instance) {
+        self.add_object_holder(ObjectHolder::from_instance(instance));
+    }
+
     pub fn objects(&self) -> &Vec<Arc<RwLock<ObjectHolder>>> {
         &self.objects
     }
 
     pub fn mut_objects(&mut self) -> &mut Vec<Arc<RwLock<ObjectHolder>>> {
+        self.bvh = None;
         &mut self.objects
     }
 
+    // Builds bounding volume hierarchies over the world's objects and inside every group that changed
+    // since its last build. Renders use whatever hierarchy is there, so call this once the scene is set up;
+    // objects edited through their locks afterwards aren't noticed, so call it again after changing them.
+    pub fn build_bvh(&mut self) {
+        let mut bounds = vec![];
+        for object in &self.objects {
+            let mut object_lock = object.write().unwrap();
+            object_lock.build_bvh();
+            bounds.push(object_lock.get_bounds());
+        }
+        self.bvh = Some(Bvh::new(&bounds));
+    }
+
     pub fn add_light(&mut self, light: Light) {
         self.lights.push(Arc::new(RwLock::new(light)));
     }
 
+    pub fn lights(&self) -> &Vec<Arc<RwLock<Light>>> {
+        &self.lights
+    }
+
+    pub fn mut_lights(&mut self) -> &mut Vec<Arc<RwLock<Light>>> {
+        &mut self.lights
+    }
+
     fn intersect_world(&self, ray: Ray) -> Vec<Intersection> {
+        let candidates = match &self.bvh {
+            Some(bvh) => bvh.candidates(ray),
+            None => (0..self.objects.len()).collect(),
+        };
         let mut intersections: Vec<Intersection> = vec![];
-        for object in &self.objects {
-            let object_lock = object.read().unwrap();
+        for index in candidates {
+            let object_lock = self.objects[index].read().unwrap();
             let object_intersections = object_lock.intersect(ray);
-            intersections.extend(object_intersections.iter());
+            intersections.extend(object_intersections);
         }
         intersections.sort_by(|a, b| a.partial_cmp(b).unwrap());
         intersections
     }
 
-    fn hit_world(&self, intersections: &Vec<Intersection>) -> Option<Intersection> {
-        if intersections.is_empty() {
-            return None;
-        }
-        for intersection in intersections {
-            if intersection.get_t() >= 0.0 {
-                return Some(*intersection);
-            }
-        }
-        None
+    fn hit_world<'a>(&self, intersections: &'a [Intersection]) -> Option<&'a Intersection> {
+        intersections.iter().find(|intersection| intersection.get_t() >= 0.0)
     }
 
-    fn shade_hit(&self, comps: Computations, remaining: usize) -> Color {
-        let shadowed = self.is_shadowed(comps.over_point);
-        let clr = lighting(comps.object.get_material(), comps.object, *self.lights[0].read().unwrap(), comps.over_point, comps.eyev, comps.normalv, shadowed);
+    fn shade_hit(&self, comps: &Computations, remaining: usize) -> Color {
+        let clr = self.surface_color(comps);
         let reflections = self.reflected_color(comps, remaining);
         let refractions = self.refracted_color(comps, remaining);
 
-        let object = comps.object;
+        let object = &comps.object;
         if object.get_reflective() > 0.0 && object.get_transparency() > 0.0 {
             let reflectance = comps.schlick();
             clr + (reflections * reflectance) + (refractions * (1.0 - reflectance))
@@ -133,21 +167,52 @@ impl World {
         }
     }
 
+    // Sums the contribution of every light. A world without lights still shows ambient color.
+    fn surface_color(&self, comps: &Computations) -> Color {
+        let material = comps.object.get_material();
+        let pattern_color = comps.object.pattern_at_object(comps.over_point);
+        if self.lights.is_empty() {
+            return pattern_color * material.get_ambient();
+        }
+
+        let mut clr = black();
+        for light in &self.lights {
+            let light = *light.read().unwrap();
+            let samples = self.visible_samples(light, comps.over_point);
+            clr += lighting_color(material, pattern_color, light, comps.over_point, comps.eyev, comps.normalv, &samples);
+        }
+        clr
+    }
+
     pub fn color_at(&self, ray: Ray, remaining: usize) -> Color {
         let intersections = self.intersect_world(ray);
         if let Some(intersection) = self.hit_world(&intersections) {
             let comps = prepare_computations(intersection, ray, &intersections);
-            self.shade_hit(comps, remaining)
+            self.shade_hit(&comps, remaining)
         } else {
             black()
         }
     }
 
-    pub fn is_shadowed(&self, point: Tuple) -> bool {
-        let light = self.lights[0].read().unwrap();
-        let vector = light.get_position() - point;
-        let distance = vector.magnitude();
-        let ray = Ray::new(point, vector.normalize());
+    // Fraction of the light's samples that are visible from the point.
+    pub fn light_visibility(&self, light: Light, point: Tuple) -> f64 {
+        self.visible_samples(light, point).iter().map(|(_, weight)| weight).sum()
+    }
+
+    // Directions towards the samples of the light that aren't blocked from the point, each weighted by
+    // its share of the light. Jittered area lights are sampled once so shading matches the shadow test.
+    fn visible_samples(&self, light: Light, point: Tuple) -> Vec<(Tuple, f64)> {
+        let samples = light.samples_from(point);
+        let weight = 1.0 / samples.len() as f64;
+        samples
+            .into_iter()
+            .filter(|(direction, distance)| !self.is_occluded(point, *direction, *distance))
+            .map(|(direction, _)| (direction, weight))
+            .collect()
+    }
+
+    fn is_occluded(&self, point: Tuple, direction: Tuple, distance: f64) -> bool {
+        let ray = Ray::new(point, direction);
         let intersections = self.intersect_world(ray);
         if let Some(hit) = self.hit_world(&intersections) {
             if hit.get_t() < distance && hit.get_object().casts_shadow() {
@@ -157,7 +222,7 @@ impl World {
         false
     }
 
-    fn reflected_color(&self, comps: Computations, remaining: usize) -> Color {
+    fn reflected_color(&self, comps: &Computations, remaining: usize) -> Color {
         if remaining < 1 {
             return black();
         }
@@ -172,7 +237,7 @@ impl World {
         clr * reflective
     }
 
-    fn refracted_color(&self, comps: Computations, remaining: usize) -> Color {
+    fn refracted_color(&self, comps: &Computations, remaining: usize) -> Color {
         if comps.object.get_transparency() == 0.0 || remaining == 0 {
             return black();
         }
@@ -192,7 +257,7 @@ impl World {
 
 /* ----------------------------------------------------------------------------------------- */
 
-#[derive(Copy, Clone)]
+#[derive(Clone)]
 struct Computations {
     object: Object,
     t_value: f64,
@@ -208,9 +273,9 @@ struct Computations {
 }
 
 impl Computations {
-    fn new(intersection: Intersection, point: Tuple, eyev: Tuple, normalv: Tuple, inside: bool, reflectv: Tuple, n1: f64, n2: f64) -> Self {
+    fn new(intersection: &Intersection, point: Tuple, eyev: Tuple, normalv: Tuple, inside: bool, reflectv: Tuple, n1: f64, n2: f64) -> Self {
         Self {
-            object: intersection.get_object(),
+            object: intersection.get_object().clone(),
             t_value: intersection.get_t(),
             point,
             eyev,
@@ -239,10 +304,10 @@ impl Computations {
     }
 }
 
-fn prepare_computations (intersection: Intersection, ray: Ray, intersection_list: &Vec<Intersection>) -> Computations {
+fn prepare_computations (intersection: &Intersection, ray: Ray, intersection_list: &Vec<Intersection>) -> Computations {
     let point = ray.position(intersection.get_t());
     let eyev = -ray.get_direction();
-    let mut normalv = intersection.get_object().normal_at(point);
+    let mut normalv = intersection.get_object().normal_at_uv(point, intersection.get_u(), intersection.get_v());
     let mut inside = false;
     if normalv * eyev < 0.0 {
         inside = true;
@@ -253,9 +318,9 @@ fn prepare_computations (intersection: Intersection, ray: Ray, intersection_list
     // Compute refraction
     let mut n1 = 1.0;
     let mut n2 = 1.0;
-    let mut containers: Vec<Object> = vec![];
+    let mut containers: Vec<&Object> = vec![];
     for i in intersection_list {
-        if i == &intersection {
+        if i == intersection {
             if containers.is_empty() {
                 n1 = 1.0;
             } else {
@@ -263,18 +328,18 @@ fn prepare_computations (intersection: Intersection, ray: Ray, intersection_list
             }
         }
 
-        let obj = &i.get_object();
-        if containers.contains(obj) {
+        let obj = i.get_object();
+        if containers.contains(&obj) {
             for x in 0..containers.len() {
-                if containers[x] == *obj {
+                if containers[x] == obj {
                     containers.remove(x);
                     break;
                 }
             }
         } else {
-            containers.push(*obj);
+            containers.push(obj);
         }
-        if i == &intersection {
+        if i == intersection {
             if containers.is_empty() {
                 n2 = 1.0;
             } else {
@@ -311,7 +376,7 @@ mod tests {
         let sphere = spheres::new();
         let intersection = Intersection::new(1.0, sphere);
 
-        let comps = prepare_computations(intersection, ray, &vec![intersection]);
+        let comps = prepare_computations(&intersection, ray, &vec![intersection.clone()]);
 
         assert_eq!(comps.point, point(0.0, 0.0, 1.0));
         assert_eq!(comps.eyev, vector(0.0, 0.0, -1.0));
@@ -319,15 +384,31 @@ mod tests {
         assert_eq!(comps.normalv, vector(0.0, 0.0, -1.0));
     }
 
+    #[test]
+    fn test_precompute_uses_uv_for_smooth_triangle() {
+        let tri = smooth_triangles::new(
+            point(0.0, 1.0, 0.0),
+            point(-1.0, 0.0, 0.0),
+            point(1.0, 0.0, 0.0),
+            vector(0.0, 1.0, 0.0),
+            vector(-1.0, 0.0, 0.0),
+            vector(1.0, 0.0, 0.0)
+        );
+        let i = Intersection::new_with_uv(1.0, tri, 0.45, 0.25);
+        let r = Ray::new(point(-0.2, 0.3, -2.0), vector(0.0, 0.0, 1.0));
+        let comps = prepare_computations(&i, r, &vec![i.clone()]);
+        assert_eq!(comps.normalv, vector(-0.5547, 0.83205, 0.0));
+    }
+
     #[test]
     fn test_precompute_offsets_point() {
         let ray = Ray::new(point(0.0, 0.0, -5.0), vector(0.0, 0.0, 1.0));
         let mut shape = spheres::new();
         shape.set_transform(translation(0.0, 0.0, 1.0));
 
-        let i = Intersection::new(5.0, shape);
+        let i = Intersection::new(5.0, shape.clone());
 
-        let comps = prepare_computations(i, ray, &vec![i]);
+        let comps = prepare_computations(&i, ray, &vec![i.clone()]);
 
         assert!(comps.over_point.z < -EPSILON/2.0);
         assert!(comps.point.z > comps.over_point.z);
@@ -337,10 +418,10 @@ mod tests {
     fn test_shade_hit() {
         let w = World::new_default();
         let r = Ray::new(point(0.0, 0.0, -5.0), vector(0.0, 0.0, 1.0));
-        if let ObjectHolder::Object(shape) = *w.objects[0].read().unwrap() {
-            let i = Intersection::new(4.0, shape);
-            let comps = prepare_computations(i, r, &vec![i]);
-            assert_eq!(w.shade_hit(comps, DEFAULT_RECURSION_DEPTH), color(0.38066, 0.47583, 0.2855));
+        if let ObjectHolder::Object(ref shape) = *w.objects[0].read().unwrap() {
+            let i = Intersection::new(4.0, shape.clone());
+            let comps = prepare_computations(&i, r, &vec![i.clone()]);
+            assert_eq!(w.shade_hit(&comps, DEFAULT_RECURSION_DEPTH), color(0.38066, 0.47583, 0.2855));
         } else {
             panic!();
         };
@@ -353,10 +434,10 @@ mod tests {
 
         let w = World::new(w_default.objects, vec![Arc::new(RwLock::new(light))]);
         let r = Ray::new(point(0.0, 0.0, 0.0), vector(0.0, 0.0, 1.0));
-        if let ObjectHolder::Object(shape) = *w.objects[1].read().unwrap() {
-            let i = Intersection::new(0.5, shape);
-            let comps = prepare_computations(i, r, &vec![i]);
-            assert_eq!(w.shade_hit(comps, DEFAULT_RECURSION_DEPTH), color(0.90498, 0.90498, 0.90498));
+        if let ObjectHolder::Object(ref shape) = *w.objects[1].read().unwrap() {
+            let i = Intersection::new(0.5, shape.clone());
+            let comps = prepare_computations(&i, r, &vec![i.clone()]);
+            assert_eq!(w.shade_hit(&comps, DEFAULT_RECURSION_DEPTH), color(0.90498, 0.90498, 0.90498));
         } else {
             panic!();
         };
@@ -374,10 +455,10 @@ mod tests {
 
         let ray = Ray::new(point(0.0, 0.0, 5.0), vector(0.0, 0.0, 1.0));
 
-        if let ObjectHolder::Object(shape) = *w.objects()[1].read().unwrap() {
-            let i = Intersection::new(4.0, shape);
-            let comps = prepare_computations(i, ray, &vec![i]);
-            let c = w.shade_hit(comps, DEFAULT_RECURSION_DEPTH);
+        if let ObjectHolder::Object(ref shape) = *w.objects()[1].read().unwrap() {
+            let i = Intersection::new(4.0, shape.clone());
+            let comps = prepare_computations(&i, ray, &vec![i.clone()]);
+            let c = w.shade_hit(&comps, DEFAULT_RECURSION_DEPTH);
             assert_eq!(c, color(0.1, 0.1, 0.1));
         } else {
             panic!();
@@ -404,7 +485,7 @@ mod tests {
     fn test_color_at_inside_sphere() {
         let mut w = World::new_default();
         if let ObjectHolder::Object(ref mut obj1) = *w.objects[0].write().unwrap() {
-            let mut sph1_material = obj1.get_material();
+            let mut sph1_material = obj1.get_material().clone();
             sph1_material.set_ambient(1.0);
             obj1.set_material(sph1_material);
         } else {
@@ -412,14 +493,14 @@ mod tests {
         };
 
         if let ObjectHolder::Object(ref mut obj2) = *w.objects[1].write().unwrap() {
-            let mut sph2_material = obj2.get_material();
+            let mut sph2_material = obj2.get_material().clone();
             sph2_material.set_ambient(1.0);
             obj2.set_material(sph2_material);
         } else {
             panic!();
         };
 
-        if let ObjectHolder::Object(obj2) = *w.objects[1].read().unwrap() {
+        if let ObjectHolder::Object(ref obj2) = *w.objects[1].read().unwrap() {
             let r = Ray::new(point(0.0, 0.0, 0.75), vector(0.0, 0.0, -1.0));
             let c = w.color_at(r, DEFAULT_RECURSION_DEPTH);
             assert_eq!(c, obj2.get_color());
@@ -431,15 +512,161 @@ mod tests {
     #[test]
     fn test_no_shadow() {
         let w = World::new_default();
-        assert!(!w.is_shadowed(point(0.0, 10.0, 0.0)));
-        assert!(!w.is_shadowed(point(-20.0, 20.0, -20.0)));
-        assert!(!w.is_shadowed(point(-2.0, 2.0, -2.0)));
+        let light = *w.lights()[0].read().unwrap();
+        assert_eq!(w.light_visibility(light, point(0.0, 10.0, 0.0)), 1.0);
+        assert_eq!(w.light_visibility(light, point(-20.0, 20.0, -20.0)), 1.0);
+        assert_eq!(w.light_visibility(light, point(-2.0, 2.0, -2.0)), 1.0);
     }
 
     #[test]
     fn test_shadowed() {
         let w = World::new_default();
-        assert!(w.is_shadowed(point(10.0, -10.0, 10.0)));
+        let light = *w.lights()[0].read().unwrap();
+        assert_eq!(w.light_visibility(light, point(10.0, -10.0, 10.0)), 0.0);
+    }
+
+    #[test]
+    fn test_shadow_tested_per_light() {
+        let mut w = World::new_default();
+        w.add_light(Light::new(point(20.0, -10.0, 10.0), white()));
+        let p = point(10.0, -10.0, 10.0);
+        let light1 = *w.lights()[0].read().unwrap();
+        let light2 = *w.lights()[1].read().unwrap();
+        assert_eq!(w.light_visibility(light1, p), 0.0);
+        assert_eq!(w.light_visibility(light2, p), 1.0);
+    }
+
+    #[test]
+    fn test_shade_hit_sums_all_lights() {
+        let mut w = World::new_default();
+        w.add_light(Light::new(point(-10.0, 10.0, -10.0), white()));
+        let r = Ray::new(point(0.0, 0.0, -5.0), vector(0.0, 0.0, 1.0));
+        if let ObjectHolder::Object(ref shape) = *w.objects[0].read().unwrap() {
+            let i = Intersection::new(4.0, shape.clone());
+            let comps = prepare_computations(&i, r, &vec![i.clone()]);
+            assert_eq!(w.shade_hit(&comps, DEFAULT_RECURSION_DEPTH), color(0.76132, 0.95166, 0.5710));
+        } else {
+            panic!();
+        };
+    }
+
+    #[test]
+    fn test_color_at_without_lights() {
+        let mut w = World::new_default();
+        w.mut_lights().clear();
+        let r = Ray::new(point(0.0, 0.0, -5.0), vector(0.0, 0.0, 1.0));
+        assert_eq!(w.color_at(r, DEFAULT_RECURSION_DEPTH), color(0.08, 0.1, 0.06));
+    }
+
+    #[test]
+    fn test_shade_hit_samples_image_texture() {
+        let mut w = World::new_from_raw(vec![], vec![]);
+        let texture = Arc::new(Texture::new(1, 1, vec![color(0.2, 0.4, 0.6)]).unwrap());
+        let mut floor = planes::new();
+        floor.set_pattern(image_texture(texture, WrapMode::Repeat)).set_ambient(1.0);
+        w.add_object(floor);
+        let r = Ray::new(point(0.3, 1.0, 0.3), vector(0.0, -1.0, 0.0));
+        assert_eq!(w.color_at(r, DEFAULT_RECURSION_DEPTH), color(0.2, 0.4, 0.6));
+    }
+
+    fn sphere_scene(n: usize) -> World {
+        let mut w = World::new_default();
+        w.add_object(planes::new());
+        let mut g = Group::new_empty();
+        for i in 0..n {
+            for j in 0..n {
+                let mut s = spheres::new();
+                s.set_transform(translation(i as f64 - 10.0, j as f64 - 10.0, 5.0) * scaling(0.4, 0.4, 0.4));
+                if i < n / 2 {
+                    w.add_object(s);
+                } else {
+                    g.add_object(s);
+                }
+            }
+        }
+        w.add_group(g);
+        w
+    }
+
+    #[test]
+    fn test_bvh_gives_same_intersections() {
+        // Built separately, since clones share their objects and building one would build both.
+        let w = sphere_scene(20);
+        let mut with_bvh = sphere_scene(20);
+        with_bvh.build_bvh();
+        for (x, y) in [(0.0, 0.0), (0.3, -0.2), (-4.0, 2.6), (9.5, 9.5), (30.0, 0.0)] {
+            let r = Ray::new(point(x, y, -5.0), vector(0.0, -0.1, 1.0).normalize());
+            let expected: Vec<f64> = w.intersect_world(r).iter().map(|i| i.get_t()).collect();
+            let actual: Vec<f64> = with_bvh.intersect_world(r).iter().map(|i| i.get_t()).collect();
+            assert_eq!(expected, actual);
+        }
+
+        let r = Ray::new(point(-4.0, 2.6, -5.0), vector(0.0, 0.0, 1.0));
+        assert!(with_bvh.bvh.as_ref().unwrap().candidates(r).len() < with_bvh.objects().len() / 10);
+    }
+
+    #[test]
+    fn test_bvh_speeds_up_intersections() {
+        let w = sphere_scene(40);
+        let mut with_bvh = sphere_scene(40);
+        with_bvh.build_bvh();
+        let rays: Vec<Ray> = (0..400)
+            .map(|i| Ray::new(point((i % 20) as f64 - 10.0, (i / 20) as f64 - 10.0, -5.0), vector(0.0, 0.0, 1.0)))
+            .collect();
+        let time = |world: &World| {
+            let start = std::time::Instant::now();
+            for r in &rays {
+                world.intersect_world(*r);
+            }
+            start.elapsed()
+        };
+
+        // Each ray passes through a handful of the 1600 spheres' boxes, so even a loaded machine should
+        // see far more than the factor of four asserted here.
+        let brute_force = time(&w);
+        let hierarchy = time(&with_bvh);
+        assert!(hierarchy * 4 < brute_force, "bvh {:?}, brute force {:?}", hierarchy, brute_force);
+    }
+
+    #[test]
+    fn test_directional_light_shadow() {
+        let w = World::new_default();
+        let light = Light::new_directional(vector(0.0, -1.0, 0.0), white());
+        assert_eq!(w.light_visibility(light, point(0.0, -10.0, 0.0)), 0.0);
+        assert_eq!(w.light_visibility(light, point(0.0, -1000.0, 0.0)), 0.0);
+        assert_eq!(w.light_visibility(light, point(2.0, -10.0, 0.0)), 1.0);
+        assert_eq!(w.light_visibility(light, point(0.0, 10.0, 0.0)), 1.0);
+    }
+
+    #[test]
+    fn test_area_light_shades_the_samples_it_tests() {
+        let mut w = World::new_from_raw(vec![], vec![]);
+        let mut blocker = cubes::new();
+        blocker.set_transform(translation(-1.0, 2.5, 0.0) * scaling(1.0, 0.1, 1.0));
+        w.add_object(blocker);
+        let mut light = Light::new_area(point(-2.0, 5.0, -0.5), vector(4.0, 0.0, 0.0), 2, vector(0.0, 0.0, 1.0), 1, white());
+        light.set_jitter(true);
+        // The left half of the light is blocked and the right half is behind the surface, so only
+        // ambient light should be left however the samples are jittered.
+        let samples = w.visible_samples(light, origin());
+        assert_eq!(samples.len(), 1);
+        assert!(samples[0].0.x > 0.0);
+        let normalv = vector(-1.0, 0.0, 0.0);
+        let c = lighting_color(&Material::new(), white(), light, origin(), normalv, normalv, &samples);
+        assert_eq!(c, color(0.1, 0.1, 0.1));
+    }
+
+    #[test]
+    fn test_area_light_visibility() {
+        let w = World::new_default();
+        let corner = point(-0.5, -0.5, -5.0);
+        let light = Light::new_area(corner, vector(1.0, 0.0, 0.0), 2, vector(0.0, 1.0, 0.0), 2, white());
+
+        assert_eq!(w.light_visibility(light, point(0.0, 0.0, 2.0)), 0.0);
+        assert_eq!(w.light_visibility(light, point(1.0, -1.0, 2.0)), 0.25);
+        assert_eq!(w.light_visibility(light, point(1.5, 0.0, 2.0)), 0.5);
+        assert_eq!(w.light_visibility(light, point(1.25, 1.25, 3.0)), 0.75);
+        assert_eq!(w.light_visibility(light, point(0.0, 0.0, -2.0)), 1.0);
     }
 
     #[test]
@@ -447,8 +674,8 @@ mod tests {
         let shape = planes::new();
         let ray = Ray::new(point(0.0, 1.0, -1.0),
                            vector(0.0, -FRAC_1_SQRT_2, FRAC_1_SQRT_2));
-        let i = Intersection::new(SQRT_2, shape);
-        let comps = prepare_computations(i, ray, &vec![i]);
+        let i = Intersection::new(SQRT_2, shape.clone());
+        let comps = prepare_computations(&i, ray, &vec![i.clone()]);
         assert_eq!(comps.reflectv, vector(0.0, FRAC_1_SQRT_2, FRAC_1_SQRT_2));
     }
 
@@ -459,9 +686,9 @@ mod tests {
         if let ObjectHolder::Object(ref mut shape) = *w.objects[1].write().unwrap() {
             shape.set_ambient(1.0);
 
-            let i = Intersection::new(1.0, *shape);
-            let comps = prepare_computations(i, r, &vec![i]);
-            let color = w.reflected_color(comps, DEFAULT_RECURSION_DEPTH);
+            let i = Intersection::new(1.0, shape.clone());
+            let comps = prepare_computations(&i, r, &vec![i.clone()]);
+            let color = w.reflected_color(&comps, DEFAULT_RECURSION_DEPTH);
 
             assert_eq!(color, black());
         } else {
@@ -475,14 +702,14 @@ mod tests {
         let mut shape = planes::new();
         shape.set_reflective(0.5);
         shape.set_transform(translation(0.0, -1.0, 0.0));
-        w.add_object(shape);
+        w.add_object(shape.clone());
 
         let r = Ray::new(point(0.0, 0.0, -3.0),
                          vector(0.0, -FRAC_1_SQRT_2, FRAC_1_SQRT_2));
-        let i = Intersection::new(SQRT_2, shape);
+        let i = Intersection::new(SQRT_2, shape.clone());
 
-        let comps = prepare_computations(i, r, &vec![i]);
-        let clr = w.reflected_color(comps, DEFAULT_RECURSION_DEPTH);
+        let comps = prepare_computations(&i, r, &vec![i.clone()]);
+        let clr = w.reflected_color(&comps, DEFAULT_RECURSION_DEPTH);
 
         assert_eq!(clr, color(0.19033, 0.23791, 0.14275));
     }
@@ -493,14 +720,14 @@ mod tests {
         let mut shape = planes::new();
         shape.set_reflective(0.5);
         shape.set_transform(translation(0.0, -1.0, 0.0));
-        w.add_object(shape);
+        w.add_object(shape.clone());
 
         let r = Ray::new(point(0.0, 0.0, -3.0),
                          vector(0.0, -FRAC_1_SQRT_2, FRAC_1_SQRT_2));
-        let i = Intersection::new(SQRT_2, shape);
+        let i = Intersection::new(SQRT_2, shape.clone());
 
-        let comps = prepare_computations(i, r, &vec![i]);
-        let clr = w.shade_hit(comps, DEFAULT_RECURSION_DEPTH);
+        let comps = prepare_computations(&i, r, &vec![i.clone()]);
+        let clr = w.shade_hit(&comps, DEFAULT_RECURSION_DEPTH);
 
         assert_eq!(clr, color(0.87676, 0.92434, 0.82918));
     }
@@ -511,14 +738,14 @@ mod tests {
         let mut shape = planes::new();
         shape.set_reflective(0.5);
         shape.set_transform(translation(0.0, -1.0, 0.0));
-        w.add_object(shape);
+        w.add_object(shape.clone());
 
         let r = Ray::new(point(0.0, 0.0, -3.0),
                          vector(0.0, -FRAC_1_SQRT_2, FRAC_1_SQRT_2));
-        let i = Intersection::new(SQRT_2, shape);
+        let i = Intersection::new(SQRT_2, shape.clone());
 
-        let comps = prepare_computations(i, r, &vec![i]);
-        let clr = w.reflected_color(comps, 0);
+        let comps = prepare_computations(&i, r, &vec![i.clone()]);
+        let clr = w.reflected_color(&comps, 0);
 
         assert_eq!(clr, black());
     }
@@ -539,29 +766,45 @@ mod tests {
 
         let r = Ray::new(point(0.0, 0.0, -4.0), vector(0.0, 0.0, 1.0));
         let xs = vec![
-            Intersection::new(2.0, a),
-            Intersection::new(2.75, b),
-            Intersection::new(3.25, c),
-            Intersection::new(4.75, b),
-            Intersection::new(5.25, c),
-            Intersection::new(6.0, a)];
-
-        let comps = prepare_computations(xs[0], r, &xs);
+            Intersection::new(2.0, a.clone()),
+            Intersection::new(2.75, b.clone()),
+            Intersection::new(3.25, c.clone()),
+            Intersection::new(4.75, b.clone()),
+            Intersection::new(5.25, c.clone()),
+            Intersection::new(6.0, a.clone())];
+
+        let comps = prepare_computations(&xs[0], r, &xs);
         assert_eq!(comps.n1, 1.0);
         assert_eq!(comps.n2, 1.5);
-        let comps = prepare_computations(xs[1], r, &xs);
+        let comps = prepare_computations(&xs[1], r, &xs);
         assert_eq!(comps.n1, 1.5);
         assert_eq!(comps.n2, 2.0);
-        let comps = prepare_computations(xs[2], r, &xs);
+        let comps = prepare_computations(&xs[2], r, &xs);
         assert_eq!(comps.n1, 2.0);
         assert_eq!(comps.n2, 2.5);
-        let comps = prepare_computations(xs[3], r, &xs);
+        let comps = prepare_computations(&xs[3], r, &xs);
         assert_eq!(comps.n1, 2.5);
         assert_eq!(comps.n2, 2.5);
-        let comps = prepare_computations(xs[4], r, &xs);
+        let comps = prepare_computations(&xs[4], r, &xs);
         assert_eq!(comps.n1, 2.5);
         assert_eq!(comps.n2, 1.5);
-        let comps = prepare_computations(xs[5], r, &xs);
+        let comps = prepare_computations(&xs[5], r, &xs);
+        assert_eq!(comps.n1, 1.5);
+        assert_eq!(comps.n2, 1.0);
+    }
+
+    #[test]
+    fn test_refractive_indices_through_glass_sdf() {
+        let mut s = sdfs::new(SdfNode::Sphere {radius: 1.0});
+        s.set_refractive_index(1.5);
+        let r = Ray::new(point(0.0, 0.0, -5.0), vector(0.0, 0.0, 1.0));
+        let xs = s.intersect(r);
+        assert_eq!(xs.len(), 2);
+
+        let comps = prepare_computations(&xs[0], r, &xs);
+        assert_eq!(comps.n1, 1.0);
+        assert_eq!(comps.n2, 1.5);
+        let comps = prepare_computations(&xs[1], r, &xs);
         assert_eq!(comps.n1, 1.5);
         assert_eq!(comps.n2, 1.0);
     }
@@ -572,10 +815,10 @@ mod tests {
         if let ObjectHolder::Object(ref mut shape) = *w.objects[0].write().unwrap() {
             let r = Ray::new(point(0.0, 0.0, -5.0), vector(0.0, 0.0, 1.0));
             let xs = vec![
-                Intersection::new(4.0, *shape),
-                Intersection::new(6.0, *shape)];
-            let comps = prepare_computations(xs[0], r, &xs);
-            let c = w.refracted_color(comps, 5);
+                Intersection::new(4.0, shape.clone()),
+                Intersection::new(6.0, shape.clone())];
+            let comps = prepare_computations(&xs[0], r, &xs);
+            let c = w.refracted_color(&comps, 5);
             assert_eq!(c, black());
         } else {
             panic!();
@@ -585,13 +828,13 @@ mod tests {
     #[test]
     fn test_refracted_color_max_recursive_depth() {
         let w = World::new_default();
-        if let ObjectHolder::Object(shape) = *w.objects[0].write().unwrap() {
+        if let ObjectHolder::Object(ref shape) = *w.objects[0].write().unwrap() {
             let r = Ray::new(point(0.0, 0.0, -5.0), vector(0.0, 0.0, 1.0));
             let xs = vec![
-                Intersection::new(4.0, shape),
-                Intersection::new(6.0, shape)];
-            let comps = prepare_computations(xs[0], r, &xs);
-            let c = w.refracted_color(comps, 0);
+                Intersection::new(4.0, shape.clone()),
+                Intersection::new(6.0, shape.clone())];
+            let comps = prepare_computations(&xs[0], r, &xs);
+            let c = w.refracted_color(&comps, 0);
             assert_eq!(c, black());
         } else {
             panic!();
@@ -601,13 +844,13 @@ mod tests {
     #[test]
     fn test_total_internal_reflection() {
         let w = World::new_default();
-        if let ObjectHolder::Object(shape) = *w.objects[0].write().unwrap() {
+        if let ObjectHolder::Object(ref shape) = *w.objects[0].write().unwrap() {
             let r = Ray::new(point(0.0, 0.0, FRAC_1_SQRT_2), vector(0.0, 1.0, 0.0));
             let xs = vec![
-                Intersection::new(-FRAC_1_SQRT_2, shape),
-                Intersection::new(FRAC_1_SQRT_2, shape)];
-            let comps = prepare_computations(xs[1], r, &xs);
-            let c = w.refracted_color(comps, 5);
+                Intersection::new(-FRAC_1_SQRT_2, shape.clone()),
+                Intersection::new(FRAC_1_SQRT_2, shape.clone())];
+            let comps = prepare_computations(&xs[1], r, &xs);
+            let c = w.refracted_color(&comps, 5);
             assert_eq!(c, black());
         } else {
             panic!();
@@ -625,16 +868,16 @@ mod tests {
             shape2.set_transparency(1.0);
             shape2.set_refractive_index(1.5);
         };
-        if let ObjectHolder::Object(shape1) = *w.objects[0].read().unwrap() {
-            if let ObjectHolder::Object(shape2) = *w.objects[1].read().unwrap() {
+        if let ObjectHolder::Object(ref shape1) = *w.objects[0].read().unwrap() {
+            if let ObjectHolder::Object(ref shape2) = *w.objects[1].read().unwrap() {
                 let r = Ray::new(point(0.0, 0.0, 0.1), vector(0.0, 1.0, 0.0));
                 let xs = vec![
-                    Intersection::new(-0.9899, shape1),
-                    Intersection::new(-0.4899, shape2),
-                    Intersection::new(0.4899, shape2),
-                    Intersection::new(0.9899, shape1)];
-                let comps = prepare_computations(xs[2], r, &xs);
-                let c = w.refracted_color(comps, 5);
+                    Intersection::new(-0.9899, shape1.clone()),
+                    Intersection::new(-0.4899, shape2.clone()),
+                    Intersection::new(0.4899, shape2.clone()),
+                    Intersection::new(0.9899, shape1.clone())];
+                let comps = prepare_computations(&xs[2], r, &xs);
+                let c = w.refracted_color(&comps, 5);
                 assert_eq!(c, color(0.0, 0.99887, 0.04722));
             } else {
                 panic!();
@@ -652,7 +895,7 @@ mod tests {
         floor.set_transform(translation(0.0, -1.0, 0.0));
         floor.set_transparency(0.5);
         floor.set_refractive_index(1.5);
-        w.add_object(floor);
+        w.add_object(floor.clone());
 
         let mut ball = spheres::new();
         ball.set_color(color(1.0, 0.0, 0.0));
@@ -662,10 +905,10 @@ mod tests {
 
         let r = Ray::new(point(0.0, 0.0, -3.0),
                          vector(0.0, -FRAC_1_SQRT_2, FRAC_1_SQRT_2));
-        let xs = vec![Intersection::new(SQRT_2, floor)];
+        let xs = vec![Intersection::new(SQRT_2, floor.clone())];
 
-        let comps = prepare_computations(xs[0], r, &xs);
-        let clr = w.shade_hit(comps, 5);
+        let comps = prepare_computations(&xs[0], r, &xs);
+        let clr = w.shade_hit(&comps, 5);
 
         assert_eq!(clr, color(0.93642, 0.68642, 0.68642));
     }
@@ -676,9 +919,9 @@ mod tests {
         let r = Ray::new(point(0.0, 0.0, FRAC_1_SQRT_2),
                          vector(0.0, 1.0, 0.0));
         let xs = vec![
-            Intersection::new(-FRAC_1_SQRT_2, shape),
-            Intersection::new(FRAC_1_SQRT_2, shape)];
-        let comps = prepare_computations(xs[1], r, &xs);
+            Intersection::new(-FRAC_1_SQRT_2, shape.clone()),
+            Intersection::new(FRAC_1_SQRT_2, shape.clone())];
+        let comps = prepare_computations(&xs[1], r, &xs);
 
         assert_eq!(comps.schlick(), 1.0);
     }
@@ -688,9 +931,9 @@ mod tests {
         let shape = spheres::glass_sphere();
         let r = Ray::new(origin(), vector(0.0, 1.0, 0.0));
         let xs = vec![
-            Intersection::new(-1.0, shape),
-            Intersection::new(1.0, shape)];
-        let comps = prepare_computations(xs[1], r, &xs);
+            Intersection::new(-1.0, shape.clone()),
+            Intersection::new(1.0, shape.clone())];
+        let comps = prepare_computations(&xs[1], r, &xs);
         let reflectance = comps.schlick();
         assert!(reflectance.approx_eq(0.04));
     }
@@ -705,7 +948,7 @@ mod tests {
         floor.set_reflective(0.5);
         floor.set_transparency(0.5);
         floor.set_refractive_index(1.5);
-        w.add_object(floor);
+        w.add_object(floor.clone());
 
         let mut ball = spheres::new();
         ball.set_color(color(1.0, 0.0, 0.0));
@@ -713,10 +956,10 @@ mod tests {
         ball.set_transform(translation(0.0, -3.5, -0.5));
         w.add_object(ball);
 
-        let xs = vec![Intersection::new(SQRT_2, floor)];
+        let xs = vec![Intersection::new(SQRT_2, floor.clone())];
 
-        let comps = prepare_computations(xs[0], r, &xs);
-        let clr = w.shade_hit(comps, 5);
+        let comps = prepare_computations(&xs[0], r, &xs);
+        let clr = w.shade_hit(&comps, 5);
 
         assert_eq!(clr, color(0.93391, 0.69643, 0.69243));
     }
@@ -725,8 +968,8 @@ mod tests {
     fn test_schlick_n2_greater_than_n1() {
         let shape = spheres::glass_sphere();
         let r = Ray::new(point(0.0, 0.99, -2.0), vector(0.0, 0.0, 1.0));
-        let xs = vec![Intersection::new(1.8589, shape)];
-        let comps = prepare_computations(xs[0], r, &xs);
+        let xs = vec![Intersection::new(1.8589, shape.clone())];
+        let comps = prepare_computations(&xs[0], r, &xs);
         let reflectance = comps.schlick();
         assert!(reflectance.approx_eq(0.48873));
     }
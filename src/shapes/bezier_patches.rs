@@ -0,0 +1,178 @@
+use crate::prelude::*;
+
+// Parameter steps below this are treated as a collapsed edge when estimating a normal.
+const DEGENERATE_NUDGE: f64 = 1e-4;
+
+// A bicubic Bézier patch given by a 4 × 4 grid of control points, stored row by row. u runs along
+// each row and v down the columns. Patches are rendered by tessellating them into smooth triangles.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BezierPatch {
+    control_points: [Tuple; 16],
+}
+
+impl BezierPatch {
+    pub fn new(control_points: [Tuple; 16]) -> Self {
+        Self {
+            control_points,
+        }
+    }
+
+    pub fn get_control_point(&self, row: usize, column: usize) -> Tuple {
+        self.control_points[row * 4 + column]
+    }
+
+    pub fn point_at(&self, u: f64, v: f64) -> Tuple {
+        origin() + self.weighted_sum(bernstein(u), bernstein(v))
+    }
+
+    // The partial derivatives along u and v span the tangent plane. Where an edge of the patch collapses
+    // to a single point one of them vanishes, so the normal is taken from just inside the patch instead.
+    pub fn normal_at(&self, u: f64, v: f64) -> Tuple {
+        let normal = self.tangent_cross(u, v);
+        if normal.magnitude() > EPSILON {
+            return normal.normalize();
+        }
+        let nudge = |t: f64| if t < 0.5 { t + DEGENERATE_NUDGE } else { t - DEGENERATE_NUDGE };
+        let normal = self.tangent_cross(nudge(u), nudge(v));
+        if normal.magnitude() > 0.0 {
+            normal.normalize()
+        } else {
+            normal
+        }
+    }
+
+    fn tangent_cross(&self, u: f64, v: f64) -> Tuple {
+        let du = self.weighted_sum(bernstein_derivative(u), bernstein(v));
+        let dv = self.weighted_sum(bernstein(u), bernstein_derivative(v));
+        dv.xprod(du)
+    }
+
+    fn weighted_sum(&self, u_weights: [f64; 4], v_weights: [f64; 4]) -> Tuple {
+        let mut sum = vector(0.0, 0.0, 0.0);
+        for (row, v_weight) in v_weights.iter().enumerate() {
+            for (column, u_weight) in u_weights.iter().enumerate() {
+                sum = sum + self.get_control_point(row, column).vectorize() * (u_weight * v_weight);
+            }
+        }
+        sum
+    }
+
+    // Splits the patch into resolution × resolution cells of two smooth triangles each. Triangles that
+    // collapse to a line at degenerate edges are left out.
+    pub fn tessellate(&self, resolution: usize) -> Group {
+        let mut group = Group::new_empty();
+        self.tessellate_into(resolution, &mut group);
+        group
+    }
+
+    pub fn tessellate_into(&self, resolution: usize, group: &mut Group) {
+        let resolution = resolution.max(1);
+        let step = 1.0 / resolution as f64;
+        // Each grid vertex's point and normal share an index in the mesh all the triangles refer to.
+        let mut mesh = Mesh::new();
+        for j in 0..=resolution {
+            for i in 0..=resolution {
+                let (u, v) = (i as f64 * step, j as f64 * step);
+                mesh.add_point(self.point_at(u, v));
+                mesh.add_normal(self.normal_at(u, v));
+            }
+        }
+        let mesh = Arc::new(mesh);
+        let vertex = |i: usize, j: usize| j * (resolution + 1) + i;
+
+        for j in 0..resolution {
+            for i in 0..resolution {
+                let corners = [vertex(i, j), vertex(i + 1, j), vertex(i + 1, j + 1), vertex(i, j + 1)];
+                for (a, b, c) in [(0, 1, 2), (0, 2, 3)] {
+                    let points = [corners[a], corners[b], corners[c]];
+                    let [p1, p2, p3] = points.map(|index| mesh.get_point(index));
+                    if (p2 - p1).xprod(p3 - p1).magnitude() > EPSILON * EPSILON {
+                        group.add_object(smooth_triangles::from_mesh(&mesh, points, points));
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn bernstein(t: f64) -> [f64; 4] {
+    let s = 1.0 - t;
+    [s * s * s, 3.0 * t * s * s, 3.0 * t * t * s, t * t * t]
+}
+
+fn bernstein_derivative(t: f64) -> [f64; 4] {
+    let s = 1.0 - t;
+    [-3.0 * s * s, 3.0 * s * s - 6.0 * t * s, 6.0 * t * s - 3.0 * t * t, 3.0 * t * t]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A patch lying flat on the xz plane over [0, 3] × [0, 3].
+    fn flat_patch() -> BezierPatch {
+        let mut points = [origin(); 16];
+        for (index, p) in points.iter_mut().enumerate() {
+            *p = point((index % 4) as f64, 0.0, (index / 4) as f64);
+        }
+        BezierPatch::new(points)
+    }
+
+    fn dome_patch() -> BezierPatch {
+        let mut points = [origin(); 16];
+        for (index, p) in points.iter_mut().enumerate() {
+            let (column, row) = ((index % 4) as f64, (index / 4) as f64);
+            let height = if (1.0..=2.0).contains(&column) && (1.0..=2.0).contains(&row) { 2.0 } else { 0.0 };
+            *p = point(column, height, row);
+        }
+        BezierPatch::new(points)
+    }
+
+    #[test]
+    fn test_patch_interpolates_corners() {
+        let p = dome_patch();
+        assert_eq!(p.point_at(0.0, 0.0), point(0.0, 0.0, 0.0));
+        assert_eq!(p.point_at(1.0, 0.0), point(3.0, 0.0, 0.0));
+        assert_eq!(p.point_at(0.0, 1.0), point(0.0, 0.0, 3.0));
+        assert_eq!(p.point_at(1.0, 1.0), point(3.0, 0.0, 3.0));
+        // Each interior control point pulls the centre up by (3/8)² × 2.
+        assert_eq!(p.point_at(0.5, 0.5), point(1.5, 4.0 * 2.0 * 9.0 / 64.0, 1.5));
+    }
+
+    #[test]
+    fn test_flat_patch_normal() {
+        let p = flat_patch();
+        assert_eq!(p.point_at(0.5, 0.25), point(1.5, 0.0, 0.75));
+        assert_eq!(p.normal_at(0.3, 0.8), vector(0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn test_dome_normals_lean_outwards() {
+        let p = dome_patch();
+        assert_eq!(p.normal_at(0.5, 0.5), vector(0.0, 1.0, 0.0));
+        let n = p.normal_at(0.1, 0.5);
+        assert!(n.x < 0.0 && n.y > 0.0 && n.z.approx_eq(0.0));
+    }
+
+    #[test]
+    fn test_collapsed_edge_normal() {
+        // Every point on the v = 0 row meets at the apex, like the top of the teapot lid.
+        let mut points = [origin(); 16];
+        for (index, p) in points.iter_mut().enumerate() {
+            let (column, row) = ((index % 4) as f64, (index / 4) as f64);
+            *p = point(column * row / 3.0, 0.0, row);
+        }
+        let p = BezierPatch::new(points);
+        assert_eq!(p.normal_at(0.5, 0.0), vector(0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn test_tessellation() {
+        let g = flat_patch().tessellate(4);
+        assert_eq!(g.elements().len(), 32);
+        let r = Ray::new(point(1.2, 1.0, 2.1), vector(0.0, -1.0, 0.0));
+        let xs = g.intersect(r);
+        assert_eq!(xs.len(), 1);
+        assert_eq!(xs[0].get_t(), 1.0);
+    }
+}
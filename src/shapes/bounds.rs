@@ -0,0 +1,224 @@
+use crate::prelude::*;
+
+// Axis-aligned bounding box. Unbounded shapes such as planes use infinite extents.
+#[derive(Copy, Clone, Debug)]
+pub struct Bounds {
+    min: Tuple,
+    max: Tuple,
+}
+
+impl PartialEq for Bounds {
+    fn eq(&self, other: &Self) -> bool {
+        self.min.x == other.min.x && self.min.y == other.min.y && self.min.z == other.min.z &&
+            self.max.x == other.max.x && self.max.y == other.max.y && self.max.z == other.max.z
+    }
+}
+
+impl Bounds {
+    pub fn new(min: Tuple, max: Tuple) -> Self {
+        Self {
+            min,
+            max,
+        }
+    }
+
+    // Contains nothing; adding a point or merging another box replaces it.
+    pub fn empty() -> Self {
+        Self {
+            min: point(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+            max: point(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
+        }
+    }
+
+    pub fn infinite() -> Self {
+        Self {
+            min: point(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
+            max: point(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+        }
+    }
+
+    pub fn get_min(&self) -> Tuple {
+        self.min
+    }
+
+    pub fn get_max(&self) -> Tuple {
+        self.max
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min.x > self.max.x || self.min.y > self.max.y || self.min.z > self.max.z
+    }
+
+    pub fn is_finite(&self) -> bool {
+        [self.min.x, self.min.y, self.min.z, self.max.x, self.max.y, self.max.z].iter().all(|v| v.is_finite())
+    }
+
+    pub fn add_point(&mut self, pt: Tuple) -> &mut Self {
+        self.min = point(self.min.x.min(pt.x), self.min.y.min(pt.y), self.min.z.min(pt.z));
+        self.max = point(self.max.x.max(pt.x), self.max.y.max(pt.y), self.max.z.max(pt.z));
+        self
+    }
+
+    pub fn merge(&mut self, other: Bounds) -> &mut Self {
+        if !other.is_empty() {
+            self.add_point(other.min);
+            self.add_point(other.max);
+        }
+        self
+    }
+
+    pub fn centre(&self) -> Tuple {
+        point(
+            (self.min.x + self.max.x) / 2.0,
+            (self.min.y + self.max.y) / 2.0,
+            (self.min.z + self.max.z) / 2.0
+        )
+    }
+
+    // Box around the transformed box, computed per axis so that infinite extents stay well defined.
+    pub fn transform(&self, matrix: Matrix4) -> Bounds {
+        if self.is_empty() {
+            return *self;
+        }
+        let min = [self.min.x, self.min.y, self.min.z];
+        let max = [self.max.x, self.max.y, self.max.z];
+        let mut new_min = [0.0; 3];
+        let mut new_max = [0.0; 3];
+        for i in 0..3 {
+            new_min[i] = matrix.val_at(i, 3);
+            new_max[i] = matrix.val_at(i, 3);
+            for j in 0..3 {
+                let m = matrix.val_at(i, j);
+                if m == 0.0 {
+                    continue;
+                }
+                let a = m * min[j];
+                let b = m * max[j];
+                new_min[i] += a.min(b);
+                new_max[i] += a.max(b);
+            }
+        }
+        Bounds::new(point(new_min[0], new_min[1], new_min[2]), point(new_max[0], new_max[1], new_max[2]))
+    }
+
+    // Slab test. Boxes entirely behind the ray origin are treated as misses.
+    pub fn intersects(&self, ray: Ray) -> bool {
+        matches!(self.clip(ray), Some((_, tmax)) if tmax >= 0.0)
+    }
+
+    // The span of t the ray spends inside the box, padded by EPSILON on every side.
+    pub fn clip(&self, ray: Ray) -> Option<(f64, f64)> {
+        if self.is_empty() {
+            return None;
+        }
+        let origin = [ray.get_origin().x, ray.get_origin().y, ray.get_origin().z];
+        let direction = [ray.get_direction().x, ray.get_direction().y, ray.get_direction().z];
+        let min = [self.min.x, self.min.y, self.min.z];
+        let max = [self.max.x, self.max.y, self.max.z];
+
+        let mut tmin = f64::NEG_INFINITY;
+        let mut tmax = f64::INFINITY;
+        for i in 0..3 {
+            if direction[i].abs() < EPSILON {
+                if origin[i] < min[i] - EPSILON || origin[i] > max[i] + EPSILON {
+                    return None;
+                }
+                continue;
+            }
+            let t1 = (min[i] - EPSILON - origin[i]) / direction[i];
+            let t2 = (max[i] + EPSILON - origin[i]) / direction[i];
+            tmin = tmin.max(t1.min(t2));
+            tmax = tmax.min(t1.max(t2));
+        }
+        if tmin <= tmax {
+            Some((tmin, tmax))
+        } else {
+            None
+        }
+    }
+}
+
+// Shapes store their limits as f64::MIN / f64::MAX when unbounded.
+pub fn extent(value: f64) -> f64 {
+    if value >= f64::MAX {
+        f64::INFINITY
+    } else if value <= f64::MIN {
+        f64::NEG_INFINITY
+    } else {
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_points() {
+        let mut b = Bounds::empty();
+        assert!(b.is_empty());
+        b.add_point(point(-5.0, 2.0, 0.0)).add_point(point(7.0, 0.0, -3.0));
+        assert_eq!(b.get_min(), point(-5.0, 0.0, -3.0));
+        assert_eq!(b.get_max(), point(7.0, 2.0, 0.0));
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut b = Bounds::new(point(-5.0, -2.0, 0.0), point(7.0, 4.0, 4.0));
+        b.merge(Bounds::new(point(8.0, -7.0, -2.0), point(14.0, 2.0, 8.0)));
+        assert_eq!(b.get_min(), point(-5.0, -7.0, -2.0));
+        assert_eq!(b.get_max(), point(14.0, 4.0, 8.0));
+    }
+
+    #[test]
+    fn test_transform() {
+        let b = Bounds::new(point(-1.0, -1.0, -1.0), point(1.0, 1.0, 1.0));
+        let t = b.transform(rotation_x(FRAC_PI_4) * rotation_y(FRAC_PI_4));
+        assert_eq!(t.get_min(), point(-SQRT_2, -1.70711, -1.70711));
+        assert_eq!(t.get_max(), point(SQRT_2, 1.70711, 1.70711));
+    }
+
+    #[test]
+    fn test_transform_infinite() {
+        let plane = Bounds::new(point(f64::NEG_INFINITY, 0.0, f64::NEG_INFINITY), point(f64::INFINITY, 0.0, f64::INFINITY));
+        let t = plane.transform(translation(0.0, 2.0, 0.0));
+        assert_eq!(t.get_min().y, 2.0);
+        assert_eq!(t.get_max().y, 2.0);
+        assert!(!t.is_finite());
+
+        let t = plane.transform(rotation_z(FRAC_PI_4));
+        assert_eq!(t.get_min().y, f64::NEG_INFINITY);
+        assert_eq!(t.get_min().z, f64::NEG_INFINITY);
+    }
+
+    #[test]
+    fn test_ray_intersects_box() {
+        let b = Bounds::new(point(5.0, -2.0, 0.0), point(11.0, 4.0, 7.0));
+        let cases = [
+            (point(15.0, 1.0, 2.0), vector(-1.0, 0.0, 0.0), true),
+            (point(-5.0, -1.0, 4.0), vector(1.0, 0.0, 0.0), true),
+            (point(7.0, 6.0, 5.0), vector(0.0, -1.0, 0.0), true),
+            (point(9.0, -5.0, 6.0), vector(0.0, 1.0, 0.0), true),
+            (point(8.0, 2.0, 12.0), vector(0.0, 0.0, -1.0), true),
+            (point(6.0, 0.0, -5.0), vector(0.0, 0.0, 1.0), true),
+            (point(8.0, 1.0, 3.5), vector(0.0, 0.0, 1.0), true),
+            (point(9.0, -1.0, -8.0), vector(2.0, 4.0, 6.0), false),
+            (point(8.0, 3.0, -4.0), vector(6.0, 2.0, 4.0), false),
+            (point(9.0, -1.0, -2.0), vector(4.0, 6.0, 3.0), false),
+            (point(4.0, 0.0, 9.0), vector(0.0, 0.0, -1.0), false),
+            (point(8.0, 6.0, -1.0), vector(0.0, -1.0, 0.0), false),
+            (point(12.0, 5.0, 4.0), vector(-1.0, 0.0, 0.0), false),
+        ];
+        for (origin, direction, expected) in cases {
+            assert_eq!(b.intersects(Ray::new(origin, direction.normalize())), expected);
+        }
+    }
+
+    #[test]
+    fn test_ray_intersects_infinite_box() {
+        let b = Bounds::new(point(f64::NEG_INFINITY, -1.0, f64::NEG_INFINITY), point(f64::INFINITY, 1.0, f64::INFINITY));
+        assert!(b.intersects(Ray::new(point(0.0, 5.0, 0.0), vector(0.0, -1.0, 0.0))));
+        assert!(b.intersects(Ray::new(point(100.0, 0.0, -30.0), vector(1.0, 0.0, 0.0))));
+        assert!(!b.intersects(Ray::new(point(0.0, 5.0, 0.0), vector(1.0, 0.0, 0.0))));
+    }
+}
@@ -0,0 +1,143 @@
+use crate::prelude::*;
+
+const LEAF_SIZE: usize = 4;
+
+#[derive(Clone, Debug, PartialEq)]
+enum BvhNode {
+    Leaf {bounds: Bounds, items: Vec<usize>},
+    Branch {bounds: Bounds, left: usize, right: usize},
+}
+
+// Bounding volume hierarchy over a list of items, identified by their index in that list.
+// Items with infinite bounds can't be partitioned and are kept aside to be tested by every ray.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bvh {
+    nodes: Vec<BvhNode>,
+    root: Option<usize>,
+    unbounded: Vec<usize>,
+}
+
+impl Bvh {
+    pub fn new(bounds: &[Bounds]) -> Self {
+        let mut bvh = Self {
+            nodes: vec![],
+            root: None,
+            unbounded: vec![],
+        };
+        let mut items = vec![];
+        for (i, b) in bounds.iter().enumerate() {
+            if b.is_finite() {
+                items.push((i, *b));
+            } else if !b.is_empty() {
+                bvh.unbounded.push(i);
+            }
+        }
+        if !items.is_empty() {
+            bvh.root = Some(bvh.build(&mut items));
+        }
+        bvh
+    }
+
+    // Splits at the median along the axis where the item centres are most spread out.
+    fn build(&mut self, items: &mut [(usize, Bounds)]) -> usize {
+        let mut bounds = Bounds::empty();
+        let mut centres = Bounds::empty();
+        for (_, b) in items.iter() {
+            bounds.merge(*b);
+            centres.add_point(b.centre());
+        }
+
+        let spread = centres.get_max() - centres.get_min();
+        if items.len() <= LEAF_SIZE || spread.magnitude() < EPSILON {
+            self.nodes.push(BvhNode::Leaf {bounds, items: items.iter().map(|(i, _)| *i).collect()});
+            return self.nodes.len() - 1;
+        }
+
+        let axis = |b: &Bounds| {
+            let c = b.centre();
+            if spread.x >= spread.y && spread.x >= spread.z {
+                c.x
+            } else if spread.y >= spread.z {
+                c.y
+            } else {
+                c.z
+            }
+        };
+        items.sort_by(|a, b| axis(&a.1).partial_cmp(&axis(&b.1)).unwrap());
+        let (left_items, right_items) = items.split_at_mut(items.len() / 2);
+        let left = self.build(left_items);
+        let right = self.build(right_items);
+        self.nodes.push(BvhNode::Branch {bounds, left, right});
+        self.nodes.len() - 1
+    }
+
+    // Indices of the items whose boxes the ray passes through, plus every unbounded item.
+    pub fn candidates(&self, ray: Ray) -> Vec<usize> {
+        let mut result = self.unbounded.clone();
+        let mut stack: Vec<usize> = self.root.into_iter().collect();
+        while let Some(index) = stack.pop() {
+            match &self.nodes[index] {
+                BvhNode::Leaf {bounds, items} => {
+                    if bounds.intersects(ray) {
+                        result.extend(items.iter());
+                    }
+                },
+                BvhNode::Branch {bounds, left, right} => {
+                    if bounds.intersects(ray) {
+                        stack.push(*left);
+                        stack.push(*right);
+                    }
+                },
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sphere_grid(n: usize) -> Vec<Object> {
+        let mut spheres = vec![];
+        for i in 0..n {
+            for j in 0..n {
+                for k in 0..n {
+                    let mut s = spheres::new();
+                    s.set_transform(translation(i as f64 * 3.0, j as f64 * 3.0, k as f64 * 3.0) * scaling(0.5, 0.5, 0.5));
+                    spheres.push(s);
+                }
+            }
+        }
+        spheres
+    }
+
+    #[test]
+    fn test_candidates_skip_missed_boxes() {
+        let objects = sphere_grid(10);
+        let bounds: Vec<Bounds> = objects.iter().map(|o| o.get_bounds()).collect();
+        let bvh = Bvh::new(&bounds);
+
+        let r = Ray::new(point(0.0, 0.0, -5.0), vector(0.0, 0.0, 1.0));
+        let candidates = bvh.candidates(r);
+        assert!(candidates.len() < objects.len() / 20);
+
+        // Every object the ray actually hits must still be a candidate.
+        for (i, object) in objects.iter().enumerate() {
+            if !object.intersect(r).is_empty() {
+                assert!(candidates.contains(&i));
+            }
+        }
+    }
+
+    #[test]
+    fn test_unbounded_items_always_candidates() {
+        let bounds = vec![
+            planes::new().get_bounds(),
+            spheres::new().get_bounds(),
+        ];
+        let bvh = Bvh::new(&bounds);
+        let r = Ray::new(point(0.0, 5.0, -5.0), vector(0.0, 0.0, 1.0));
+        assert_eq!(bvh.candidates(r), vec![0]);
+    }
+}
@@ -8,7 +8,7 @@ pub fn new_unbounded() -> Object {
     Object::new(Shape::Cone {min: f64::MIN, max: f64::MAX, closed: false})
 }
 
-fn min(cone: Object) -> f64 {
+fn min(cone: &Object) -> f64 {
     if let Shape::Cone {min, max: _, closed: _} = cone.shape {
         min
     } else {
@@ -16,7 +16,7 @@ fn min(cone: Object) -> f64 {
     }
 }
 
-fn max(cone: Object) -> f64 {
+fn max(cone: &Object) -> f64 {
     if let Shape::Cone {min: _, max, closed: _} = cone.shape {
         max
     } else {
@@ -24,7 +24,14 @@ fn max(cone: Object) -> f64 {
     }
 }
 
-pub fn intersect(cone: Object, ray: Ray) -> Vec<Intersection> {
+// The radius of a cone at height y is |y|, so the widest point is at whichever end is further from the apex.
+pub fn local_bounds(cone: &Object) -> Bounds {
+    let (low, high) = (extent(min(cone)), extent(max(cone)));
+    let radius = low.abs().max(high.abs());
+    Bounds::new(point(-radius, low, -radius), point(radius, high, radius))
+}
+
+pub fn intersect(cone: &Object, ray: Ray) -> Vec<Intersection> {
     let mut vec = vec![];
 
     let a = ray.get_direction().x.powi(2) -
@@ -46,7 +53,7 @@ pub fn intersect(cone: Object, ray: Ray) -> Vec<Intersection> {
 
     if a.approx_eq(0.0) {
         let t = -c / (2.0 * b);
-        vec.push(Intersection::new(t, cone));
+        vec.push(Intersection::new(t, cone.clone()));
         intersect_caps(cone, ray, &mut vec);
         return vec;
     }
@@ -69,31 +76,31 @@ pub fn intersect(cone: Object, ray: Ray) -> Vec<Intersection> {
     let y0 = ray.get_origin().y + (t0 * ray.get_direction().y);
 
     if min(cone) < y0 && y0 < max(cone) {
-        vec.push(Intersection::new(t0, cone));
+        vec.push(Intersection::new(t0, cone.clone()));
     }
 
     let y1 = ray.get_origin().y + (t1 * ray.get_direction().y);
     if min(cone) < y1 && y1 < max(cone) {
-        vec.push(Intersection::new(t1, cone));
+        vec.push(Intersection::new(t1, cone.clone()));
     }
 
     intersect_caps(cone, ray, &mut vec);
     vec
 }
 
-fn intersect_caps(cone: Object, ray: Ray, intersections: &mut Vec<Intersection>) {
+fn intersect_caps(cone: &Object, ray: Ray, intersections: &mut Vec<Intersection>) {
     if !is_closed(cone) || ray.get_direction().y.approx_eq(0.0) {
         return;
     }
 
     let t1 = (min(cone) - ray.get_origin().y) / ray.get_direction().y;
     if check_cap(ray, t1, min(cone)) {
-        intersections.push(Intersection::new(t1, cone));
+        intersections.push(Intersection::new(t1, cone.clone()));
     }
 
     let t2 = (max(cone) - ray.get_origin().y) / ray.get_direction().y;
     if check_cap(ray, t2, max(cone)) {
-        intersections.push(Intersection::new(t2, cone));
+        intersections.push(Intersection::new(t2, cone.clone()));
     }
 }
 
@@ -103,7 +110,7 @@ fn check_cap(ray: Ray, t: f64, y: f64) -> bool {
     x.powi(2) + z.powi(2) <= y.abs()
 }
 
-fn is_closed(cone: Object) -> bool {
+fn is_closed(cone: &Object) -> bool {
     if let Shape::Cone {min: _, max: _, closed} = cone.shape {
         closed
     } else {
@@ -111,7 +118,7 @@ fn is_closed(cone: Object) -> bool {
     }
 }
 
-pub fn normal_at(cone: Object, point: Tuple) -> Tuple {
+pub fn normal_at(cone: &Object, point: Tuple) -> Tuple {
     let dist_to_y_axis_sq = point.x.powi(2) + point.z.powi(2);
     if dist_to_y_axis_sq < 1.0 && point.y >= max(cone) - EPSILON {
         vector(0.0, 1.0, 0.0)
@@ -181,13 +188,13 @@ mod tests {
     fn test_normal() {
         let cone = cones::new_unbounded();
 
-        let n = normal_at(cone, origin());
+        let n = normal_at(&cone, origin());
         assert_eq!(n, vector(0.0, 0.0, 0.0));
 
-        let n = normal_at(cone, point(1.0, 1.0, 1.0));
+        let n = normal_at(&cone, point(1.0, 1.0, 1.0));
         assert_eq!(n, vector(1.0, -SQRT_2, 1.0));
 
-        let n = normal_at(cone, point(-1.0, -1.0, 0.0));
+        let n = normal_at(&cone, point(-1.0, -1.0, 0.0));
         assert_eq!(n, vector(-1.0, 1.0, 0.0));
     }
 }
\ No newline at end of file
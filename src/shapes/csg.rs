@@ -0,0 +1,307 @@
+use crate::prelude::*;
+use crate::shapes::traits::Transformable;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum CsgOperation {
+    Union,
+    Intersection,
+    Difference,
+}
+
+impl std::fmt::Display for CsgOperation {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match *self {
+            CsgOperation::Union => write!(f, "Union"),
+            CsgOperation::Intersection => write!(f, "Intersection"),
+            CsgOperation::Difference => write!(f, "Difference"),
+        }
+    }
+}
+
+// Constructive solid geometry node: the difference keeps the parts of the left child outside the right one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Csg {
+    operation: CsgOperation,
+    left: Box<ObjectHolder>,
+    right: Box<ObjectHolder>,
+    transform: Matrix4,
+    parent_transform: Matrix4,
+    inverse_transform: Matrix4,
+    inverse_transform_transposed: Matrix4,
+    transformations_list: [f64; 15],
+}
+
+impl Csg {
+    pub fn new(operation: CsgOperation, mut left: ObjectHolder, mut right: ObjectHolder) -> Self {
+        left.set_parent_transform(Matrix4::identity());
+        right.set_parent_transform(Matrix4::identity());
+        Self {
+            operation,
+            left: Box::new(left),
+            right: Box::new(right),
+            transform: Matrix4::identity(),
+            parent_transform: Matrix4::identity(),
+            inverse_transform: Matrix4::identity(),
+            inverse_transform_transposed: Matrix4::identity(),
+            transformations_list: [
+                0.0, 0.0, 0.0,
+                1.0, 1.0, 1.0,
+                0.0, 0.0, 0.0,
+                0.0, 0.0, 0.0,
+                0.0, 0.0, 0.0
+            ],
+        }
+    }
+
+    pub fn get_operation(&self) -> CsgOperation {
+        self.operation
+    }
+
+    pub fn set_operation(&mut self, operation: CsgOperation) -> &mut Self {
+        self.operation = operation;
+        self
+    }
+
+    pub fn left(&self) -> &ObjectHolder {
+        &self.left
+    }
+
+    pub fn right(&self) -> &ObjectHolder {
+        &self.right
+    }
+
+    pub fn mut_left(&mut self) -> &mut ObjectHolder {
+        &mut self.left
+    }
+
+    pub fn mut_right(&mut self) -> &mut ObjectHolder {
+        &mut self.right
+    }
+
+    // A union of both children's bounds, which also covers intersections and differences.
+    pub fn get_bounds(&self) -> Bounds {
+        let mut bounds = self.left.get_bounds();
+        bounds.merge(self.right.get_bounds());
+        bounds
+    }
+
+    fn update_world_transform(&mut self) {
+        let world = self.get_world_transform();
+        self.inverse_transform = world.invert();
+        self.inverse_transform_transposed = self.inverse_transform.transpose();
+        self.left.set_parent_transform(world);
+        self.right.set_parent_transform(world);
+    }
+
+    pub fn build_bvh(&mut self) {
+        self.left.build_bvh();
+        self.right.build_bvh();
+    }
+
+    /* --------------------------- ray tracing calculations --------------------------- */
+
+    pub fn intersect(&self, ray: Ray) -> Vec<Intersection> {
+        let mut xs: Vec<(Intersection, bool)> = vec![];
+        for i in self.left.intersect(ray) {
+            xs.push((i, true));
+        }
+        for i in self.right.intersect(ray) {
+            xs.push((i, false));
+        }
+        xs.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+        self.filter_intersections(&xs).into_iter().map(|(i, _)| i).collect()
+    }
+
+    // Walks the sorted hits of both children, tracking whether the ray is inside each of them.
+    fn filter_intersections(&self, xs: &[(Intersection, bool)]) -> Vec<(Intersection, bool)> {
+        let mut inside_left = false;
+        let mut inside_right = false;
+        let mut result = vec![];
+        for (i, left_hit) in xs {
+            if intersection_allowed(self.operation, *left_hit, inside_left, inside_right) {
+                result.push((i.clone(), *left_hit));
+            }
+            if *left_hit {
+                inside_left = !inside_left;
+            } else {
+                inside_right = !inside_right;
+            }
+        }
+        result
+    }
+}
+
+pub fn intersection_allowed(operation: CsgOperation, left_hit: bool, inside_left: bool, inside_right: bool) -> bool {
+    match operation {
+        CsgOperation::Union => (left_hit && !inside_right) || (!left_hit && !inside_left),
+        CsgOperation::Intersection => (left_hit && inside_right) || (!left_hit && inside_left),
+        CsgOperation::Difference => (left_hit && !inside_right) || (!left_hit && inside_left),
+    }
+}
+
+impl Transformable for Csg {
+    fn set_transform(&mut self, transform: Matrix4) {
+        self.transform = transform;
+        self.update_world_transform();
+    }
+
+    fn set_parent_transform(&mut self, parent: Matrix4) {
+        self.parent_transform = parent;
+        self.update_world_transform();
+    }
+
+    fn set_transformation_list(&mut self, index: usize, x: f64) {
+        self.transformations_list[index] = x;
+        self.update_transform();
+    }
+
+    fn get_transform(&self) -> Matrix4 {
+        self.transform
+    }
+
+    fn get_parent_transform(&self) -> Matrix4 {
+        self.parent_transform
+    }
+
+    fn get_inverse_transform(&self) -> Matrix4 {
+        self.inverse_transform
+    }
+
+    fn get_inverse_transform_transposed(&self) -> Matrix4 {
+        self.inverse_transform_transposed
+    }
+
+    fn get_transformation_list(&self, index: usize) -> f64 {
+        self.transformations_list[index]
+    }
+
+    fn transformation_list_all(&self) -> [f64; 15] {
+        self.transformations_list
+    }
+
+    fn transformation_list_ref(&mut self) -> &mut [f64; 15] {
+        &mut self.transformations_list
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_union_rules() {
+        let expected = [
+            (true, true, true, false),
+            (true, true, false, true),
+            (true, false, true, false),
+            (true, false, false, true),
+            (false, true, true, false),
+            (false, true, false, false),
+            (false, false, true, true),
+            (false, false, false, true),
+        ];
+        for (lhit, inl, inr, result) in expected {
+            assert_eq!(intersection_allowed(CsgOperation::Union, lhit, inl, inr), result);
+        }
+    }
+
+    #[test]
+    fn test_intersection_rules() {
+        let expected = [
+            (true, true, true, true),
+            (true, true, false, false),
+            (true, false, true, true),
+            (true, false, false, false),
+            (false, true, true, true),
+            (false, true, false, true),
+            (false, false, true, false),
+            (false, false, false, false),
+        ];
+        for (lhit, inl, inr, result) in expected {
+            assert_eq!(intersection_allowed(CsgOperation::Intersection, lhit, inl, inr), result);
+        }
+    }
+
+    #[test]
+    fn test_difference_rules() {
+        let expected = [
+            (true, true, true, false),
+            (true, true, false, true),
+            (true, false, true, false),
+            (true, false, false, true),
+            (false, true, true, true),
+            (false, true, false, true),
+            (false, false, true, false),
+            (false, false, false, false),
+        ];
+        for (lhit, inl, inr, result) in expected {
+            assert_eq!(intersection_allowed(CsgOperation::Difference, lhit, inl, inr), result);
+        }
+    }
+
+    #[test]
+    fn test_filtering_intersections() {
+        let s1 = spheres::new();
+        let s2 = cubes::new();
+        let xs = [
+            (Intersection::new(1.0, s1.clone()), true),
+            (Intersection::new(2.0, s2.clone()), false),
+            (Intersection::new(3.0, s1.clone()), true),
+            (Intersection::new(4.0, s2.clone()), false),
+        ];
+        let expected = [
+            (CsgOperation::Union, 0, 3),
+            (CsgOperation::Intersection, 1, 2),
+            (CsgOperation::Difference, 0, 1),
+        ];
+        for (operation, x0, x1) in expected {
+            let c = Csg::new(operation, ObjectHolder::from_object(s1.clone()), ObjectHolder::from_object(s2.clone()));
+            let result = c.filter_intersections(&xs);
+            assert_eq!(result.len(), 2);
+            assert_eq!(result[0].0.get_t(), xs[x0].0.get_t());
+            assert_eq!(result[1].0.get_t(), xs[x1].0.get_t());
+        }
+    }
+
+    #[test]
+    fn test_ray_misses_csg() {
+        let c = Csg::new(CsgOperation::Union,
+                         ObjectHolder::from_object(spheres::new()),
+                         ObjectHolder::from_object(cubes::new()));
+        let r = Ray::new(point(0.0, 2.0, -5.0), vector(0.0, 0.0, 1.0));
+        assert!(c.intersect(r).is_empty());
+    }
+
+    #[test]
+    fn test_ray_hits_csg() {
+        let s1 = spheres::new();
+        let mut s2 = spheres::new();
+        s2.translate_z(0.5);
+        let c = Csg::new(CsgOperation::Union, ObjectHolder::from_object(s1.clone()), ObjectHolder::from_object(s2.clone()));
+        let r = Ray::new(point(0.0, 0.0, -5.0), vector(0.0, 0.0, 1.0));
+        let xs = c.intersect(r);
+        assert_eq!(xs.len(), 2);
+        assert_eq!(xs[0].get_t(), 4.0);
+        assert_eq!(xs[0].get_object(), &s1);
+        assert_eq!(xs[1].get_t(), 6.5);
+        assert_eq!(xs[1].get_object(), &s2);
+    }
+
+    #[test]
+    fn test_cube_minus_cylinder() {
+        let cube = cubes::new();
+        let mut hole = cylinders::new(-2.0, 2.0, true);
+        hole.scale_x(0.5);
+        hole.scale_z(0.5);
+        let c = Csg::new(CsgOperation::Difference, ObjectHolder::from_object(cube), ObjectHolder::from_object(hole));
+
+        let r = Ray::new(point(0.0, 5.0, 0.0), vector(0.0, -1.0, 0.0));
+        assert!(c.intersect(r).is_empty());
+
+        let r = Ray::new(point(0.0, 0.0, -5.0), vector(0.0, 0.0, 1.0));
+        let xs = c.intersect(r);
+        let ts: Vec<f64> = xs.iter().map(|i| i.get_t()).collect();
+        assert_eq!(ts, vec![4.0, 4.5, 5.5, 6.0]);
+    }
+}
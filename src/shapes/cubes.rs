@@ -4,7 +4,11 @@ pub fn new() -> Object {
     Object::new(Shape::Cube)
 }
 
-pub fn intersect(cube: Object, ray: Ray) -> Vec<Intersection> {
+pub fn local_bounds() -> Bounds {
+    Bounds::new(point(-1.0, -1.0, -1.0), point(1.0, 1.0, 1.0))
+}
+
+pub fn intersect(cube: &Object, ray: Ray) -> Vec<Intersection> {
     let mut vec = vec![];
     let (xtmin, xtmax) = check_axis(ray.get_origin().x, ray.get_direction().x);
 
@@ -23,8 +27,8 @@ pub fn intersect(cube: Object, ray: Ray) -> Vec<Intersection> {
         return vec;
     }
 
-    vec.push(Intersection::new(tmin, cube));
-    vec.push(Intersection::new(tmax, cube));
+    vec.push(Intersection::new(tmin, cube.clone()));
+    vec.push(Intersection::new(tmax, cube.clone()));
     vec
 }
 
@@ -71,43 +75,43 @@ mod tests {
         let c = cubes::new();
 
         let r = Ray::new(point(5.0, 0.5, 0.0), vector(-1.0, 0.0, 0.0));
-        let xs = intersect(c, r);
+        let xs = intersect(&c, r);
         assert_eq!(xs.len(), 2);
         assert_eq!(xs[0].get_t(), 4.0);
         assert_eq!(xs[1].get_t(), 6.0);
 
         let r = Ray::new(point(-5.0, 0.5, 0.0), vector(1.0, 0.0, 0.0));
-        let xs = intersect(c, r);
+        let xs = intersect(&c, r);
         assert_eq!(xs.len(), 2);
         assert_eq!(xs[0].get_t(), 4.0);
         assert_eq!(xs[1].get_t(), 6.0);
 
         let r = Ray::new(point(0.5, 5.0, 0.0), vector(0.0, -1.0, 0.0));
-        let xs = intersect(c, r);
+        let xs = intersect(&c, r);
         assert_eq!(xs.len(), 2);
         assert_eq!(xs[0].get_t(), 4.0);
         assert_eq!(xs[1].get_t(), 6.0);
 
         let r = Ray::new(point(0.5, -5.0, 0.0), vector(0.0, 1.0, 0.0));
-        let xs = intersect(c, r);
+        let xs = intersect(&c, r);
         assert_eq!(xs.len(), 2);
         assert_eq!(xs[0].get_t(), 4.0);
         assert_eq!(xs[1].get_t(), 6.0);
 
         let r = Ray::new(point(0.5, 0.0, 5.0), vector(0.0, 0.0, -1.0));
-        let xs = intersect(c, r);
+        let xs = intersect(&c, r);
         assert_eq!(xs.len(), 2);
         assert_eq!(xs[0].get_t(), 4.0);
         assert_eq!(xs[1].get_t(), 6.0);
 
         let r = Ray::new(point(0.5, 0.0, -5.0), vector(0.0, 0.0, 1.0));
-        let xs = intersect(c, r);
+        let xs = intersect(&c, r);
         assert_eq!(xs.len(), 2);
         assert_eq!(xs[0].get_t(), 4.0);
         assert_eq!(xs[1].get_t(), 6.0);
 
         let r = Ray::new(point(0.0, 0.5, 0.0), vector(0.0, 0.0, 1.0));
-        let xs = intersect(c, r);
+        let xs = intersect(&c, r);
         assert_eq!(xs.len(), 2);
         assert_eq!(xs[0].get_t(), -1.0);
         assert_eq!(xs[1].get_t(), 1.0);
@@ -118,27 +122,27 @@ mod tests {
         let c = cubes::new();
 
         let r = Ray::new(point(-2.0, 0.0, 0.0), vector(0.2673, 0.5345, 0.8018));
-        let xs = intersect(c, r);
+        let xs = intersect(&c, r);
         assert_eq!(xs.len(), 0);
 
         let r = Ray::new(point(0.0, -2.0, 0.0), vector(0.8018, 0.2673, 0.5345));
-        let xs = intersect(c, r);
+        let xs = intersect(&c, r);
         assert_eq!(xs.len(), 0);
 
         let r = Ray::new(point(0.0, 0.0, -2.0), vector(0.5345, 0.8018, 0.2673));
-        let xs = intersect(c, r);
+        let xs = intersect(&c, r);
         assert_eq!(xs.len(), 0);
 
         let r = Ray::new(point(2.0, 0.0, 2.0), vector(0.0, 0.0, -1.0));
-        let xs = intersect(c, r);
+        let xs = intersect(&c, r);
         assert_eq!(xs.len(), 0);
 
         let r = Ray::new(point(0.0, 2.0, 2.0), vector(0.0, -1.0, 0.0));
-        let xs = intersect(c, r);
+        let xs = intersect(&c, r);
         assert_eq!(xs.len(), 0);
 
         let r = Ray::new(point(2.0, 2.0, 0.0), vector(-1.0, 0.0, 0.0));
-        let xs = intersect(c, r);
+        let xs = intersect(&c, r);
         assert_eq!(xs.len(), 0);
     }
 
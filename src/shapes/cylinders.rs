@@ -8,7 +8,7 @@ pub fn new_unbounded() -> Object {
     Object::new(Shape::Cylinder {min: f64::MIN, max: f64::MAX, closed: false})
 }
 
-pub fn max(cylinder: Object) -> f64 {
+pub fn max(cylinder: &Object) -> f64 {
     if let Shape::Cylinder {min: _, max, closed: _} = cylinder.shape {
         max
     } else {
@@ -16,7 +16,7 @@ pub fn max(cylinder: Object) -> f64 {
     }
 }
 
-pub fn min(cylinder: Object) -> f64 {
+pub fn min(cylinder: &Object) -> f64 {
     if let Shape::Cylinder {min, max: _, closed: _} = cylinder.shape {
         min
     } else {
@@ -24,7 +24,11 @@ pub fn min(cylinder: Object) -> f64 {
     }
 }
 
-pub fn intersect(cylinder: Object, ray: Ray) -> Vec<Intersection> {
+pub fn local_bounds(cylinder: &Object) -> Bounds {
+    Bounds::new(point(-1.0, extent(min(cylinder)), -1.0), point(1.0, extent(max(cylinder)), 1.0))
+}
+
+pub fn intersect(cylinder: &Object, ray: Ray) -> Vec<Intersection> {
     let mut vec = vec![];
     let a = ray.get_direction().x.powi(2) + ray.get_direction().z.powi(2);
     if a < EPSILON {
@@ -51,31 +55,31 @@ pub fn intersect(cylinder: Object, ray: Ray) -> Vec<Intersection> {
 
     let y0 = ray.get_origin().y + (t0 * ray.get_direction().y);
     if min(cylinder) < y0 && y0 < max(cylinder) {
-        vec.push(Intersection::new(t0, cylinder));
+        vec.push(Intersection::new(t0, cylinder.clone()));
     }
 
     let y1 = ray.get_origin().y + (t1 * ray.get_direction().y);
     if min(cylinder) < y1 && y1 < max(cylinder) {
-        vec.push(Intersection::new(t1, cylinder));
+        vec.push(Intersection::new(t1, cylinder.clone()));
     }
 
     intersect_caps(cylinder, ray, &mut vec);
     vec
 }
 
-fn intersect_caps(cylinder: Object, ray: Ray, intersections: &mut Vec<Intersection>) {
+fn intersect_caps(cylinder: &Object, ray: Ray, intersections: &mut Vec<Intersection>) {
     if !is_closed(cylinder) || ray.get_direction().y.approx_eq(0.0) {
         return;
     }
 
     let t1 = (min(cylinder) - ray.get_origin().y) / ray.get_direction().y;
     if check_cap(ray, t1) {
-        intersections.push(Intersection::new(t1, cylinder));
+        intersections.push(Intersection::new(t1, cylinder.clone()));
     }
 
     let t2 = (max(cylinder) - ray.get_origin().y) / ray.get_direction().y;
     if check_cap(ray, t2) {
-        intersections.push(Intersection::new(t2, cylinder));
+        intersections.push(Intersection::new(t2, cylinder.clone()));
     }
 }
 
@@ -85,7 +89,7 @@ fn check_cap(ray: Ray, t: f64) -> bool {
     x.powi(2) + z.powi(2) <= 1.0
 }
 
-fn is_closed(cylinder: Object) -> bool {
+fn is_closed(cylinder: &Object) -> bool {
     if let Shape::Cylinder {min: _, max: _, closed} = cylinder.shape {
         closed
     } else {
@@ -93,7 +97,7 @@ fn is_closed(cylinder: Object) -> bool {
     }
 }
 
-pub fn normal_at(cylinder: Object, point: Tuple) -> Tuple {
+pub fn normal_at(cylinder: &Object, point: Tuple) -> Tuple {
     let dist_to_y_axis_sq = point.x.powi(2) + point.z.powi(2);
     if dist_to_y_axis_sq < 1.0 && point.y >= max(cylinder) - EPSILON {
         vector(0.0, 1.0, 0.0)
@@ -220,22 +224,22 @@ mod tests {
     fn test_normal_at_end_caps() {
         let cyl = cylinders::new(1.0, 2.0, true);
 
-        let n = normal_at(cyl, point(0.0, 1.0, 0.0));
+        let n = normal_at(&cyl, point(0.0, 1.0, 0.0));
         assert_eq!(n, vector(0.0, -1.0, 0.0));
 
-        let n = normal_at(cyl, point(0.5, 1.0, 0.0));
+        let n = normal_at(&cyl, point(0.5, 1.0, 0.0));
         assert_eq!(n, vector(0.0, -1.0, 0.0));
 
-        let n = normal_at(cyl, point(0.0, 1.0, 0.5));
+        let n = normal_at(&cyl, point(0.0, 1.0, 0.5));
         assert_eq!(n, vector(0.0, -1.0, 0.0));
 
-        let n = normal_at(cyl, point(0.0, 2.0, 0.0));
+        let n = normal_at(&cyl, point(0.0, 2.0, 0.0));
         assert_eq!(n, vector(0.0, 1.0, 0.0));
 
-        let n = normal_at(cyl, point(0.5, 2.0, 0.0));
+        let n = normal_at(&cyl, point(0.5, 2.0, 0.0));
         assert_eq!(n, vector(0.0, 1.0, 0.0));
 
-        let n = normal_at(cyl, point(0.0, 2.0, 0.5));
+        let n = normal_at(&cyl, point(0.0, 2.0, 0.5));
         assert_eq!(n, vector(0.0, 1.0, 0.0));
     }
 }
\ No newline at end of file
@@ -0,0 +1,84 @@
+use crate::prelude::*;
+
+// A disk in the xz plane facing +y. A non-zero inner radius cuts a hole out of the middle,
+// turning it into an annulus.
+pub fn new(radius: f64) -> Object {
+    Object::new(Shape::Disk {radius, inner: 0.0})
+}
+
+pub fn new_annulus(radius: f64, inner: f64) -> Object {
+    Object::new(Shape::Disk {radius, inner})
+}
+
+fn radii(disk: &Object) -> (f64, f64) {
+    if let Shape::Disk {radius, inner} = disk.shape {
+        (radius, inner)
+    } else {
+        (1.0, 0.0)
+    }
+}
+
+pub fn normal_at() -> Tuple {
+    vector(0.0, 1.0, 0.0)
+}
+
+pub fn local_bounds(disk: &Object) -> Bounds {
+    let (radius, _) = radii(disk);
+    Bounds::new(point(-radius, 0.0, -radius), point(radius, 0.0, radius))
+}
+
+pub fn intersect(disk: &Object, ray: Ray) -> Vec<Intersection> {
+    if ray.get_direction().y.approx_eq(0.0) {
+        return vec![]
+    }
+    let t = -ray.get_origin().y / ray.get_direction().y;
+    let x = ray.get_origin().x + t * ray.get_direction().x;
+    let z = ray.get_origin().z + t * ray.get_direction().z;
+    let dist_sq = x.powi(2) + z.powi(2);
+
+    let (radius, inner) = radii(disk);
+    if dist_sq > radius.powi(2) || dist_sq < inner.powi(2) {
+        return vec![]
+    }
+    vec![Intersection::new(t, disk.clone())]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ray_hits_disk() {
+        let d = new(1.0);
+        let r = Ray::new(point(0.5, 1.0, 0.5), vector(0.0, -1.0, 0.0));
+        let xs = intersect(&d, r);
+        assert_eq!(xs.len(), 1);
+        assert_eq!(xs[0].get_t(), 1.0);
+        assert_eq!(xs[0].get_object(), &d);
+    }
+
+    #[test]
+    fn test_ray_misses_disk() {
+        let d = new(1.0);
+        let outside = Ray::new(point(1.0, 1.0, 0.5), vector(0.0, -1.0, 0.0));
+        let parallel = Ray::new(point(0.0, 0.0, -5.0), vector(0.0, 0.0, 1.0));
+        assert!(intersect(&d, outside).is_empty());
+        assert!(intersect(&d, parallel).is_empty());
+    }
+
+    #[test]
+    fn test_ray_through_annulus_hole() {
+        let d = new_annulus(1.0, 0.5);
+        let hole = Ray::new(point(0.25, 1.0, 0.0), vector(0.0, -1.0, 0.0));
+        let ring = Ray::new(point(0.75, 1.0, 0.0), vector(0.0, -1.0, 0.0));
+        assert!(intersect(&d, hole).is_empty());
+        assert_eq!(intersect(&d, ring).len(), 1);
+    }
+
+    #[test]
+    fn test_disk_bounds() {
+        let b = local_bounds(&new(2.0));
+        assert_eq!(b.get_min(), point(-2.0, 0.0, -2.0));
+        assert_eq!(b.get_max(), point(2.0, 0.0, 2.0));
+    }
+}
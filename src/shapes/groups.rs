@@ -5,9 +5,11 @@ use crate::shapes::traits::Transformable;
 pub struct Group {
     group: Vec<ObjectHolder>,
     transform: Matrix4,
+    parent_transform: Matrix4,
     inverse_transform: Matrix4,
     inverse_transform_transposed: Matrix4,
     transformations_list: [f64; 15],
+    bvh: Option<Bvh>,
 }
 
 impl Group {
@@ -15,6 +17,7 @@ impl Group {
         Self {
             group,
             transform: Matrix4::identity(),
+            parent_transform: Matrix4::identity(),
             inverse_transform: Matrix4::identity(),
             inverse_transform_transposed: Matrix4::identity(),
             transformations_list: [
@@ -24,6 +27,7 @@ impl Group {
                 0.0, 0.0, 0.0,
                 0.0, 0.0, 0.0
             ],
+            bvh: None,
         }
     }
 
@@ -31,6 +35,7 @@ impl Group {
         Self {
             group: vec![],
             transform: Matrix4::identity(),
+            parent_transform: Matrix4::identity(),
             inverse_transform: Matrix4::identity(),
             inverse_transform_transposed: Matrix4::identity(),
             transformations_list: [
@@ -40,19 +45,22 @@ impl Group {
                 0.0, 0.0, 0.0,
                 0.0, 0.0, 0.0
             ],
+            bvh: None,
         }
     }
 
-    pub fn add_element(&mut self, element: ObjectHolder) {
+    pub fn add_element(&mut self, mut element: ObjectHolder) {
+        self.bvh = None;
+        element.set_parent_transform(self.get_world_transform());
         self.group.push(element);
     }
 
     pub fn add_object(&mut self, object: Object) {
-        self.group.push(ObjectHolder::from_object(object));
+        self.add_element(ObjectHolder::from_object(object));
     }
 
     pub fn add_group(&mut self, group: Group) {
-        self.group.push(ObjectHolder::from_group(group));
+        self.add_element(ObjectHolder::from_group(group));
     }
 
     pub fn elements(&self) -> &Vec<ObjectHolder> {
@@ -60,24 +68,53 @@ impl Group {
     }
 
     pub fn mut_elements(&mut self) -> &mut Vec<ObjectHolder> {
+        self.bvh = None;
         &mut self.group
     }
 
-    /* --------------------------- ray tracing calculations --------------------------- */
+    pub fn get_bounds(&self) -> Bounds {
+        let mut bounds = Bounds::empty();
+        for element in &self.group {
+            bounds.merge(element.get_bounds());
+        }
+        bounds
+    }
 
-    pub fn intersect(&self, ray: Ray) -> Vec<Intersection> {
-        let local_ray = ray.transform(self.get_inverse_transform());
-        self.local_intersect(local_ray)
+    // Members hold their world transforms already, so they're handed the new one rather than
+    // having the group's transform applied to every ray and hit.
+    fn update_world_transform(&mut self) {
+        let world = self.get_world_transform();
+        self.inverse_transform = world.invert();
+        self.inverse_transform_transposed = self.inverse_transform.transpose();
+        for element in &mut self.group {
+            element.set_parent_transform(world);
+        }
+        self.bvh = None;
     }
 
-    fn local_intersect(&self, ray: Ray) -> Vec<Intersection> {
+    // Builds hierarchies for nested groups first, then one over this group's members.
+    // Changing the members through mut_elements discards it; until then building again does nothing.
+    pub fn build_bvh(&mut self) {
+        if self.bvh.is_some() {
+            return;
+        }
+        for element in &mut self.group {
+            element.build_bvh();
+        }
+        let bounds: Vec<Bounds> = self.group.iter().map(|e| e.get_bounds()).collect();
+        self.bvh = Some(Bvh::new(&bounds));
+    }
+
+    /* --------------------------- ray tracing calculations --------------------------- */
+
+    pub fn intersect(&self, ray: Ray) -> Vec<Intersection> {
+        let members: Vec<&ObjectHolder> = match &self.bvh {
+            Some(bvh) => bvh.candidates(ray).into_iter().map(|i| &self.group[i]).collect(),
+            None => self.group.iter().collect(),
+        };
         let mut vec = vec![];
-        for object in &self.group {
-            for i in object.intersect(ray) {
-                let mut new_obj = i.get_object();
-                new_obj.combine_transforms(self.transformations_list);
-                vec.push(Intersection::new(i.get_t(), new_obj));
-            }
+        for object in members {
+            vec.extend(object.intersect(ray));
         }
         vec.sort_by(|a, b| a.partial_cmp(b).unwrap());
         vec
@@ -87,8 +124,12 @@ impl Group {
 impl Transformable for Group {
     fn set_transform(&mut self, transform: Matrix4) {
         self.transform = transform;
-        self.inverse_transform = transform.invert();
-        self.inverse_transform_transposed = self.inverse_transform.transpose();
+        self.update_world_transform();
+    }
+
+    fn set_parent_transform(&mut self, parent: Matrix4) {
+        self.parent_transform = parent;
+        self.update_world_transform();
     }
 
     fn set_transformation_list(&mut self, index: usize, x: f64) {
@@ -100,6 +141,10 @@ impl Transformable for Group {
         self.transform
     }
 
+    fn get_parent_transform(&self) -> Matrix4 {
+        self.parent_transform
+    }
+
     fn get_inverse_transform(&self) -> Matrix4 {
         self.inverse_transform
     }
@@ -166,10 +211,10 @@ mod tests {
         let xs = g.intersect(r);
 
         assert_eq!(xs.len(), 4);
-        assert_eq!(xs[0].get_object(), *s_2.get_object().unwrap());
-        assert_eq!(xs[1].get_object(), *s_2.get_object().unwrap());
-        assert_eq!(xs[2].get_object(), *s_1.get_object().unwrap());
-        assert_eq!(xs[3].get_object(), *s_1.get_object().unwrap());
+        assert_eq!(xs[0].get_object(), s_2.get_object().unwrap());
+        assert_eq!(xs[1].get_object(), s_2.get_object().unwrap());
+        assert_eq!(xs[2].get_object(), s_1.get_object().unwrap());
+        assert_eq!(xs[3].get_object(), s_1.get_object().unwrap());
     }
 
     #[test]
@@ -190,4 +235,68 @@ mod tests {
         let xs = g.intersect(r);
         assert_eq!(xs.len(), 2);
     }
-}
\ No newline at end of file
+
+    fn nested_sphere(g1: &Group) -> Object {
+        let g2 = &g1.elements()[0];
+        g2.get_group_members().unwrap()[0].get_object().unwrap().clone()
+    }
+
+    fn nested_groups(inner_scaling: Matrix4) -> Group {
+        let mut s = spheres::new();
+        s.set_transform(translation(5.0, 0.0, 0.0));
+        let mut g2 = Group::new_empty();
+        g2.set_transform(inner_scaling);
+        g2.add_object(s);
+        let mut g1 = Group::new_empty();
+        g1.set_transform(rotation_y(FRAC_PI_2));
+        g1.add_group(g2);
+        g1
+    }
+
+    #[test]
+    fn test_world_to_object_space() {
+        let s = nested_sphere(&nested_groups(scaling(2.0, 2.0, 2.0)));
+        assert_eq!(s.get_transform(), translation(5.0, 0.0, 0.0));
+        assert_eq!(s.get_inverse_transform() * point(-2.0, 0.0, -10.0), point(0.0, 0.0, -1.0));
+    }
+
+    #[test]
+    fn test_normal_on_child_object() {
+        let s = nested_sphere(&nested_groups(scaling(1.0, 2.0, 3.0)));
+        let n = s.normal_at(point(1.7321, 1.1547, -5.5774));
+        assert!((n - vector(0.2857, 0.4286, -0.8571)).magnitude() < 0.0001);
+    }
+
+    #[test]
+    fn test_transform_set_after_children_added() {
+        let mut s = spheres::new();
+        s.set_transform(translation(5.0, 0.0, 0.0));
+        let mut g2 = Group::new_empty();
+        g2.add_object(s);
+        let mut g1 = Group::new_empty();
+        g1.add_group(g2);
+
+        g1.set_transform(rotation_y(FRAC_PI_2));
+        g1.mut_elements()[0].set_transform(scaling(2.0, 2.0, 2.0));
+
+        let s = nested_sphere(&g1);
+        assert_eq!(s.get_inverse_transform() * point(-2.0, 0.0, -10.0), point(0.0, 0.0, -1.0));
+    }
+
+    #[test]
+    fn test_rotated_group_rotates_arrangement() {
+        let mut g = Group::new_empty();
+        let mut s = spheres::new();
+        s.translate_x(5.0);
+        g.add_object(s);
+        g.rotate_y(FRAC_PI_2);
+
+        // The sphere now sits on the negative z axis rather than being spun in place.
+        let r = Ray::new(point(0.0, 0.0, -10.0), vector(0.0, 0.0, 1.0));
+        let xs = g.intersect(r);
+        assert_eq!(xs.len(), 2);
+        assert_eq!(xs[0].get_t(), 4.0);
+        assert_eq!(xs[1].get_t(), 6.0);
+        assert_eq!(xs[0].get_object().normal_at(point(0.0, 0.0, -6.0)), vector(0.0, 0.0, -1.0));
+    }
+}
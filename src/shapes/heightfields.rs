@@ -0,0 +1,331 @@
+use crate::prelude::*;
+use crate::shapes::traits::Transformable;
+use std::path::Path;
+
+// A terrain surface over the unit square in xz, with heights between 0 and 1 sampled on a grid of
+// columns × rows. The surface object carries the material and transforms shared by every cell; a hit
+// swaps its shape for the smooth triangle that was struck, so shading goes through the usual paths.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Heightfield {
+    surface: Object,
+    heights: Arc<Vec<f64>>,
+    columns: usize,
+    rows: usize,
+    min_height: f64,
+    max_height: f64,
+}
+
+impl Heightfield {
+    pub fn new(columns: usize, rows: usize, heights: Vec<f64>) -> Result<Self, &'static str> {
+        if columns < 2 || rows < 2 {
+            return Err("A heightfield needs at least two samples along each axis");
+        }
+        if heights.len() != columns * rows {
+            return Err("Number of heights does not match the grid size");
+        }
+        let min_height = heights.iter().cloned().fold(f64::INFINITY, f64::min);
+        let max_height = heights.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        Ok(Self {
+            surface: planes::new(),
+            heights: Arc::new(heights),
+            columns,
+            rows,
+            min_height,
+            max_height,
+        })
+    }
+
+    // Image x runs along the field's x axis and image y along its z axis.
+    pub fn from_luma(image: &image::GrayImage) -> Result<Self, &'static str> {
+        let heights = image.pixels().map(|p| p[0] as f64 / 255.0).collect();
+        Heightfield::new(image.width() as usize, image.height() as usize, heights)
+    }
+
+    pub fn from_image_file<P: AsRef<Path>>(path: P) -> image::ImageResult<Self> {
+        let image = image::open(path)?.to_luma8();
+        Heightfield::from_luma(&image).map_err(|message| {
+            image::ImageError::Parameter(image::error::ParameterError::from_kind(
+                image::error::ParameterErrorKind::Generic(message.to_string())
+            ))
+        })
+    }
+
+    pub fn get_columns(&self) -> usize {
+        self.columns
+    }
+
+    pub fn get_rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn get_height(&self, column: usize, row: usize) -> f64 {
+        self.heights[row * self.columns + column]
+    }
+
+    pub fn get_material(&self) -> &Material {
+        self.surface.get_material()
+    }
+
+    pub fn set_material(&mut self, material: Material) -> &mut Self {
+        self.surface.set_material(material);
+        self
+    }
+
+    pub fn get_bounds(&self) -> Bounds {
+        Bounds::new(point(0.0, self.min_height, 0.0), point(1.0, self.max_height, 1.0))
+            .transform(self.get_world_transform())
+    }
+
+    /* --------------------------- ray tracing calculations --------------------------- */
+
+    // Works in grid space, where each cell is a unit square in xz, and walks only the cells the ray
+    // passes over. Scaling x and z keeps t unchanged and stops small cells from looking degenerate.
+    pub fn intersect(&self, ray: Ray) -> Vec<Intersection> {
+        let grid_ray = self.to_grid_space(ray.transform(self.surface.get_inverse_transform()));
+
+        let mut xs = vec![];
+        let (t_enter, t_exit) = match self.clip(grid_ray) {
+            Some(range) => range,
+            None => return xs,
+        };
+
+        let origin = grid_ray.get_origin();
+        let direction = grid_ray.get_direction();
+        let entry_x = origin.x + t_enter * direction.x;
+        let entry_z = origin.z + t_enter * direction.z;
+        let mut column = (entry_x.floor() as isize).clamp(0, self.columns as isize - 2);
+        let mut row = (entry_z.floor() as isize).clamp(0, self.rows as isize - 2);
+
+        let (step_x, mut next_x, delta_x) = traversal_axis(origin.x, direction.x, column);
+        let (step_z, mut next_z, delta_z) = traversal_axis(origin.z, direction.z, row);
+
+        loop {
+            self.intersect_cell(column as usize, row as usize, grid_ray, &mut xs);
+            if next_x < next_z {
+                if next_x > t_exit {
+                    break;
+                }
+                column += step_x;
+                next_x += delta_x;
+            } else {
+                if next_z > t_exit {
+                    break;
+                }
+                row += step_z;
+                next_z += delta_z;
+            }
+            if column < 0 || row < 0 || column > self.columns as isize - 2 || row > self.rows as isize - 2 {
+                break;
+            }
+        }
+        xs
+    }
+
+    fn to_grid_space(&self, local_ray: Ray) -> Ray {
+        let (cells_x, cells_z) = ((self.columns - 1) as f64, (self.rows - 1) as f64);
+        let origin = local_ray.get_origin();
+        let direction = local_ray.get_direction();
+        Ray::new(
+            point(origin.x * cells_x, origin.y, origin.z * cells_z),
+            vector(direction.x * cells_x, direction.y, direction.z * cells_z)
+        )
+    }
+
+    // The span of t the ray spends inside the grid's bounding box.
+    fn clip(&self, ray: Ray) -> Option<(f64, f64)> {
+        let (cells_x, cells_z) = ((self.columns - 1) as f64, (self.rows - 1) as f64);
+        Bounds::new(point(0.0, self.min_height, 0.0), point(cells_x, self.max_height, cells_z)).clip(ray)
+    }
+
+    fn intersect_cell(&self, column: usize, row: usize, grid_ray: Ray, xs: &mut Vec<Intersection>) {
+        let corners = [(column, row), (column + 1, row), (column + 1, row + 1), (column, row + 1)];
+        for (a, b, c) in [(0, 1, 2), (0, 2, 3)] {
+            let (ca, cb, cc) = (corners[a], corners[b], corners[c]);
+            let grid_point = |(i, j): (usize, usize)| point(i as f64, self.get_height(i, j), j as f64);
+            let g1 = grid_point(ca);
+            let hit = triangles::intersect_barycentric(g1, grid_point(cb) - g1, grid_point(cc) - g1, grid_ray);
+            if let Some((t, u, v)) = hit {
+                // A ray crossing a shared edge would otherwise report the same point twice.
+                if xs.last().is_some_and(|last| last.get_t().approx_eq(t)) {
+                    continue;
+                }
+                let mut object = self.surface.clone();
+                object.shape = smooth_triangles::new(
+                    self.vertex(ca), self.vertex(cb), self.vertex(cc),
+                    self.vertex_normal(ca), self.vertex_normal(cb), self.vertex_normal(cc)
+                ).shape;
+                xs.push(Intersection::new_with_uv(t, object, u, v));
+            }
+        }
+    }
+
+    fn vertex(&self, (column, row): (usize, usize)) -> Tuple {
+        point(
+            column as f64 / (self.columns - 1) as f64,
+            self.get_height(column, row),
+            row as f64 / (self.rows - 1) as f64
+        )
+    }
+
+    // Central differences of the heights, falling back to one-sided differences along the edges.
+    fn vertex_normal(&self, (column, row): (usize, usize)) -> Tuple {
+        let (left, right) = (column.saturating_sub(1), (column + 1).min(self.columns - 1));
+        let (back, front) = (row.saturating_sub(1), (row + 1).min(self.rows - 1));
+        let dx = (self.get_height(right, row) - self.get_height(left, row)) * (self.columns - 1) as f64 /
+            (right - left) as f64;
+        let dz = (self.get_height(column, front) - self.get_height(column, back)) * (self.rows - 1) as f64 /
+            (front - back) as f64;
+        vector(-dx, 1.0, -dz).normalize()
+    }
+}
+
+// Which way the ray steps along one grid axis, the t of its first cell boundary and the t between boundaries.
+fn traversal_axis(origin: f64, direction: f64, cell: isize) -> (isize, f64, f64) {
+    if direction > 0.0 {
+        (1, ((cell + 1) as f64 - origin) / direction, 1.0 / direction)
+    } else if direction < 0.0 {
+        (-1, (cell as f64 - origin) / direction, -1.0 / direction)
+    } else {
+        (0, f64::INFINITY, f64::INFINITY)
+    }
+}
+
+impl Transformable for Heightfield {
+    fn set_transform(&mut self, transform: Matrix4) {
+        self.surface.set_transform(transform);
+    }
+
+    fn set_parent_transform(&mut self, parent: Matrix4) {
+        self.surface.set_parent_transform(parent);
+    }
+
+    fn set_transformation_list(&mut self, index: usize, x: f64) {
+        self.surface.set_transformation_list(index, x);
+    }
+
+    fn get_transform(&self) -> Matrix4 {
+        self.surface.get_transform()
+    }
+
+    fn get_parent_transform(&self) -> Matrix4 {
+        self.surface.get_parent_transform()
+    }
+
+    fn get_inverse_transform(&self) -> Matrix4 {
+        self.surface.get_inverse_transform()
+    }
+
+    fn get_inverse_transform_transposed(&self) -> Matrix4 {
+        self.surface.get_inverse_transform_transposed()
+    }
+
+    fn get_transformation_list(&self, index: usize) -> f64 {
+        self.surface.get_transformation_list(index)
+    }
+
+    fn transformation_list_all(&self) -> [f64; 15] {
+        self.surface.transformation_list_all()
+    }
+
+    fn transformation_list_ref(&mut self) -> &mut [f64; 15] {
+        self.surface.transformation_list_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    fn ramp() -> Heightfield {
+        // Three columns rising from 0 to 1 along x, so the surface is the plane y = x.
+        Heightfield::new(3, 2, vec![0.0, 0.5, 1.0, 0.0, 0.5, 1.0]).unwrap()
+    }
+
+    #[test]
+    fn test_grid_must_match_heights() {
+        assert!(Heightfield::new(1, 4, vec![0.0; 4]).is_err());
+        assert!(Heightfield::new(2, 2, vec![0.0; 3]).is_err());
+    }
+
+    #[test]
+    fn test_ray_hits_flat_heightfield() {
+        let h = Heightfield::new(2, 2, vec![0.5; 4]).unwrap();
+        let r = Ray::new(point(0.3, 5.0, 0.7), vector(0.0, -1.0, 0.0));
+        let xs = h.intersect(r);
+        assert_eq!(xs.len(), 1);
+        assert!(xs[0].get_t().approx_eq(4.5));
+        let normal = xs[0].get_object().normal_at_uv(r.position(4.5), xs[0].get_u(), xs[0].get_v());
+        assert_eq!(normal, vector(0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn test_normal_follows_gradient() {
+        let h = ramp();
+        let r = Ray::new(point(0.25, 5.0, 0.5), vector(0.0, -1.0, 0.0));
+        let xs = h.intersect(r);
+        assert_eq!(xs.len(), 1);
+        assert!(xs[0].get_t().approx_eq(4.75));
+        let normal = xs[0].get_object().normal_at_uv(r.position(4.75), xs[0].get_u(), xs[0].get_v());
+        assert_eq!(normal, vector(-FRAC_1_SQRT_2, FRAC_1_SQRT_2, 0.0));
+    }
+
+    #[test]
+    fn test_ray_misses_heightfield() {
+        let h = ramp();
+        let outside = Ray::new(point(1.5, 5.0, 0.5), vector(0.0, -1.0, 0.0));
+        let above = Ray::new(point(-1.0, 2.0, 0.5), vector(1.0, 0.0, 0.0));
+        assert!(h.intersect(outside).is_empty());
+        assert!(h.intersect(above).is_empty());
+    }
+
+    #[test]
+    fn test_transformed_heightfield() {
+        let mut h = ramp();
+        h.set_transform(scaling(10.0, 2.0, 10.0));
+        let r = Ray::new(point(5.0, 10.0, 5.0), vector(0.0, -1.0, 0.0));
+        let xs = h.intersect(r);
+        assert_eq!(xs.len(), 1);
+        assert!(xs[0].get_t().approx_eq(9.0));
+        assert_eq!(h.get_bounds().get_max(), point(10.0, 2.0, 10.0));
+    }
+
+    #[test]
+    fn test_traversal_matches_every_cell() {
+        let (columns, rows) = (9, 7);
+        let heights = (0..columns * rows).map(|i| ((i as f64 * 0.37).sin() + 1.0) / 2.0).collect();
+        let h = Heightfield::new(columns, rows, heights).unwrap();
+        let rays = [
+            Ray::new(point(-0.5, 1.5, -0.3), vector(1.0, -0.8, 0.9)),
+            Ray::new(point(1.4, 0.6, 0.2), vector(-1.0, 0.1, 0.3)),
+            Ray::new(point(0.3, 2.0, 1.5), vector(0.2, -1.0, -0.9)),
+            Ray::new(point(0.51, 0.5, -1.0), vector(0.0, 0.0, 1.0)),
+        ];
+        for r in rays {
+            let mut expected = vec![];
+            for row in 0..rows - 1 {
+                for column in 0..columns - 1 {
+                    h.intersect_cell(column, row, h.to_grid_space(r), &mut expected);
+                }
+            }
+            let mut expected: Vec<f64> = expected.iter().map(|i| i.get_t()).collect();
+            expected.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            expected.dedup_by(|a, b| a.approx_eq(*b));
+            let actual: Vec<f64> = h.intersect(r).iter().map(|i| i.get_t()).collect();
+            assert!(!actual.is_empty());
+            assert_eq!(actual.len(), expected.len());
+            for (a, e) in actual.iter().zip(expected) {
+                assert!(a.approx_eq(e));
+            }
+        }
+    }
+
+    #[test]
+    fn test_heights_from_image() {
+        let image = image::GrayImage::from_raw(2, 2, vec![0, 51, 255, 102]).unwrap();
+        let h = Heightfield::from_luma(&image).unwrap();
+        assert_eq!(h.get_columns(), 2);
+        assert_eq!(h.get_rows(), 2);
+        assert!(h.get_height(1, 0).approx_eq(0.2));
+        assert!(h.get_height(0, 1).approx_eq(1.0));
+    }
+}
@@ -0,0 +1,200 @@
+use crate::prelude::*;
+use crate::shapes::traits::Transformable;
+
+// Places a shared prototype group in the scene with its own transform, so many copies of a mesh cost
+// one set of triangles. The prototype can't be changed once shared; hits on it are moved out to the
+// instance's placement and optionally given the instance's material.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Instance {
+    prototype: Arc<Group>,
+    material: Option<Material>,
+    transform: Matrix4,
+    parent_transform: Matrix4,
+    world_transform: Matrix4,
+    inverse_transform: Matrix4,
+    inverse_transform_transposed: Matrix4,
+    transformations_list: [f64; 15],
+}
+
+impl Instance {
+    pub fn new(prototype: Arc<Group>) -> Self {
+        Self {
+            prototype,
+            material: None,
+            transform: Matrix4::identity(),
+            parent_transform: Matrix4::identity(),
+            world_transform: Matrix4::identity(),
+            inverse_transform: Matrix4::identity(),
+            inverse_transform_transposed: Matrix4::identity(),
+            transformations_list: [
+                0.0, 0.0, 0.0,
+                1.0, 1.0, 1.0,
+                0.0, 0.0, 0.0,
+                0.0, 0.0, 0.0,
+                0.0, 0.0, 0.0
+            ],
+        }
+    }
+
+    // Prepares a group for sharing: it's moved to the origin of its instances' space and its bounding
+    // volume hierarchy is built now, since it can't be rebuilt once other instances hold it.
+    pub fn prototype(mut group: Group) -> Arc<Group> {
+        group.set_parent_transform(Matrix4::identity());
+        group.build_bvh();
+        Arc::new(group)
+    }
+
+    pub fn get_prototype(&self) -> &Arc<Group> {
+        &self.prototype
+    }
+
+    pub fn get_material_override(&self) -> Option<&Material> {
+        self.material.as_ref()
+    }
+
+    pub fn set_material_override(&mut self, material: Option<Material>) -> &mut Self {
+        self.material = material;
+        self
+    }
+
+    pub fn get_bounds(&self) -> Bounds {
+        self.prototype.get_bounds().transform(self.world_transform)
+    }
+
+    fn update_world_transform(&mut self) {
+        self.world_transform = self.get_world_transform();
+        self.inverse_transform = self.world_transform.invert();
+        self.inverse_transform_transposed = self.inverse_transform.transpose();
+    }
+
+    /* --------------------------- ray tracing calculations --------------------------- */
+
+    pub fn intersect(&self, ray: Ray) -> Vec<Intersection> {
+        let mut xs = self.prototype.intersect(ray.transform(self.inverse_transform));
+        for i in &mut xs {
+            let object = i.mut_object();
+            object.apply_outer_transform(self.world_transform, self.inverse_transform);
+            if let Some(material) = &self.material {
+                object.set_material(material.clone());
+            }
+        }
+        xs
+    }
+}
+
+impl Transformable for Instance {
+    fn set_transform(&mut self, transform: Matrix4) {
+        self.transform = transform;
+        self.update_world_transform();
+    }
+
+    fn set_parent_transform(&mut self, parent: Matrix4) {
+        self.parent_transform = parent;
+        self.update_world_transform();
+    }
+
+    fn set_transformation_list(&mut self, index: usize, x: f64) {
+        self.transformations_list[index] = x;
+        self.update_transform();
+    }
+
+    fn get_transform(&self) -> Matrix4 {
+        self.transform
+    }
+
+    fn get_parent_transform(&self) -> Matrix4 {
+        self.parent_transform
+    }
+
+    fn get_inverse_transform(&self) -> Matrix4 {
+        self.inverse_transform
+    }
+
+    fn get_inverse_transform_transposed(&self) -> Matrix4 {
+        self.inverse_transform_transposed
+    }
+
+    fn get_transformation_list(&self, index: usize) -> f64 {
+        self.transformations_list[index]
+    }
+
+    fn transformation_list_all(&self) -> [f64; 15] {
+        self.transformations_list
+    }
+
+    fn transformation_list_ref(&mut self) -> &mut [f64; 15] {
+        &mut self.transformations_list
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    fn tree() -> Group {
+        let mut trunk = cylinders::new(0.0, 2.0, true);
+        trunk.set_transform(scaling(0.2, 1.0, 0.2));
+        let mut crown = spheres::new();
+        crown.set_transform(translation(0.0, 3.0, 0.0));
+        let mut g = Group::new_empty();
+        g.add_object(trunk);
+        g.add_object(crown);
+        g
+    }
+
+    #[test]
+    fn test_instances_share_prototype() {
+        let prototype = Instance::prototype(tree());
+        let a = Instance::new(prototype.clone());
+        let b = Instance::new(prototype.clone());
+        assert!(Arc::ptr_eq(a.get_prototype(), b.get_prototype()));
+        assert_eq!(Arc::strong_count(&prototype), 3);
+    }
+
+    #[test]
+    fn test_instance_matches_transformed_copy() {
+        let transform = translation(5.0, 0.0, -2.0) * rotation_y(FRAC_PI_4) * scaling(2.0, 2.0, 2.0);
+        let mut instance = Instance::new(Instance::prototype(tree()));
+        instance.set_transform(transform);
+        let mut copy = tree();
+        copy.set_transform(transform);
+
+        let r = Ray::new(point(5.0, 6.0, -10.0), vector(0.0, 0.0, 1.0));
+        let expected = copy.intersect(r);
+        let actual = instance.intersect(r);
+        assert_eq!(actual.len(), 2);
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(&expected) {
+            assert!(a.get_t().approx_eq(e.get_t()));
+            let p = r.position(a.get_t());
+            assert_eq!(a.get_object().normal_at(p), e.get_object().normal_at(p));
+        }
+    }
+
+    #[test]
+    fn test_instance_inside_group() {
+        let mut instance = Instance::new(Instance::prototype(tree()));
+        instance.set_transform(translation(0.0, 0.0, 3.0));
+        let mut g = Group::new_empty();
+        g.set_transform(translation(4.0, 0.0, 0.0));
+        g.add_element(ObjectHolder::from_instance(instance));
+
+        let r = Ray::new(point(4.0, 3.0, -5.0), vector(0.0, 0.0, 1.0));
+        let xs = g.intersect(r);
+        assert_eq!(xs.len(), 2);
+        assert!(xs[0].get_t().approx_eq(7.0));
+        assert_eq!(xs[0].get_object().normal_at(point(4.0, 3.0, 2.0)), vector(0.0, 0.0, -1.0));
+        assert_eq!(g.get_bounds().get_max(), point(5.0, 4.0, 4.0));
+    }
+
+    #[test]
+    fn test_material_override() {
+        let mut red = Material::new();
+        red.set_color(color(1.0, 0.0, 0.0));
+        let mut instance = Instance::new(Instance::prototype(tree()));
+        let r = Ray::new(point(0.0, 3.0, -5.0), vector(0.0, 0.0, 1.0));
+        assert_eq!(instance.intersect(r)[0].get_object().get_material(), &Material::new());
+        instance.set_material_override(Some(red.clone()));
+        assert_eq!(instance.intersect(r)[0].get_object().get_material(), &red);
+    }
+}
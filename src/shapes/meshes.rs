@@ -0,0 +1,83 @@
+use crate::prelude::*;
+
+// Vertex positions and normals shared by the triangles of a model. Each triangle keeps the mesh and the
+// indices of its corners, so a triangle object stays small however large the model it came from.
+#[derive(Clone, Debug, Default)]
+pub struct Mesh {
+    points: Vec<Tuple>,
+    normals: Vec<Tuple>,
+}
+
+// Meshes compare by identity, so checking whether two triangles are the same never walks the model.
+impl PartialEq for Mesh {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl Mesh {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Returns the index triangles use to refer to the point.
+    pub fn add_point(&mut self, point: Tuple) -> usize {
+        self.points.push(point);
+        self.points.len() - 1
+    }
+
+    pub fn add_normal(&mut self, normal: Tuple) -> usize {
+        self.normals.push(normal);
+        self.normals.len() - 1
+    }
+
+    pub fn get_point(&self, index: usize) -> Tuple {
+        self.points[index]
+    }
+
+    pub fn get_normal(&self, index: usize) -> Tuple {
+        self.normals[index]
+    }
+
+    pub fn point_count(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn normal_count(&self) -> usize {
+        self.normals.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_triangles_share_a_mesh() {
+        let mut mesh = Mesh::new();
+        for p in [point(0.0, 1.0, 0.0), point(-1.0, 0.0, 0.0), point(1.0, 0.0, 0.0), point(0.0, -1.0, 0.0)] {
+            mesh.add_point(p);
+        }
+        mesh.add_normal(vector(0.0, 0.0, -1.0));
+        let mesh = Arc::new(mesh);
+        let upper = triangles::from_mesh(&mesh, [0, 1, 2], 0);
+        let lower = triangles::from_mesh(&mesh, [3, 2, 1], 0);
+        assert_eq!(triangles::corners(&lower), [point(0.0, -1.0, 0.0), point(1.0, 0.0, 0.0), point(-1.0, 0.0, 0.0)]);
+        assert_eq!(upper.normal_at(point(0.0, 0.5, 0.0)), vector(0.0, 0.0, -1.0));
+        assert_ne!(upper, lower);
+        assert_eq!(Arc::strong_count(&mesh), 3);
+    }
+
+    #[test]
+    fn test_meshes_compare_by_identity() {
+        let (p1, p2, p3) = (point(0.0, 1.0, 0.0), point(-1.0, 0.0, 0.0), point(1.0, 0.0, 0.0));
+        let t = triangles::new(p1, p2, p3);
+        assert_eq!(t, t.clone());
+        assert_ne!(t, triangles::new(p1, p2, p3));
+    }
+
+    #[test]
+    fn test_triangle_shapes_stay_small() {
+        assert!(std::mem::size_of::<Shape>() <= 64, "Shape is {} bytes", std::mem::size_of::<Shape>());
+    }
+}
@@ -0,0 +1,290 @@
+use crate::prelude::*;
+
+// A point charge whose field falls smoothly from its strength at the centre to zero at its radius
+// of influence. Negative strengths carve dents into neighbouring blobs.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Charge {
+    centre: Tuple,
+    radius: f64,
+    strength: f64,
+}
+
+impl Charge {
+    pub fn new(centre: Tuple, radius: f64, strength: f64) -> Result<Self, &'static str> {
+        if radius.is_nan() || radius <= 0.0 {
+            return Err("A charge needs a positive radius of influence");
+        }
+        Ok(Self {
+            centre,
+            radius,
+            strength,
+        })
+    }
+
+    pub fn get_centre(&self) -> Tuple {
+        self.centre
+    }
+
+    pub fn get_radius(&self) -> f64 {
+        self.radius
+    }
+
+    pub fn get_strength(&self) -> f64 {
+        self.strength
+    }
+
+    // strength * (1 - r²/R²)³, which has zero slope at the edge of influence so blobs merge smoothly.
+    fn field(&self, p: Tuple) -> f64 {
+        let falloff = 1.0 - (p - self.centre).magnitude().powi(2) / self.radius.powi(2);
+        if falloff <= 0.0 {
+            0.0
+        } else {
+            self.strength * falloff.powi(3)
+        }
+    }
+
+    fn gradient(&self, p: Tuple) -> Tuple {
+        let offset = p - self.centre;
+        let falloff = 1.0 - offset.magnitude().powi(2) / self.radius.powi(2);
+        if falloff <= 0.0 {
+            vector(0.0, 0.0, 0.0)
+        } else {
+            offset * (-6.0 * self.strength * falloff.powi(2) / self.radius.powi(2))
+        }
+    }
+
+    // The field along the ray as a polynomial in s = t - t0, highest power first. The falloff is a
+    // quadratic in s, so the field is the strength times its cube.
+    fn field_polynomial(&self, ray: Ray, t0: f64) -> [f64; 7] {
+        let (to_ray, direction) = (ray.position(t0) - self.centre, ray.get_direction());
+        let radius_sq = self.radius.powi(2);
+        let falloff = [
+            -(direction * direction) / radius_sq,
+            -2.0 * (direction * to_ray) / radius_sq,
+            1.0 - (to_ray * to_ray) / radius_sq,
+        ];
+        let mut cube = [0.0; 7];
+        for (i, a) in falloff.iter().enumerate() {
+            for (j, b) in falloff.iter().enumerate() {
+                for (k, c) in falloff.iter().enumerate() {
+                    cube[i + j + k] += self.strength * a * b * c;
+                }
+            }
+        }
+        cube
+    }
+
+    // The span of t the ray spends inside the sphere of influence.
+    fn span(&self, ray: Ray) -> Option<(f64, f64)> {
+        let to_ray = ray.get_origin() - self.centre;
+        let a = ray.get_direction() * ray.get_direction();
+        let b = 2.0 * (ray.get_direction() * to_ray);
+        let c = to_ray * to_ray - self.radius.powi(2);
+        match solve_quadratic(a, b, c)[..] {
+            [t0, t1] => Some((t0, t1)),
+            _ => None,
+        }
+    }
+}
+
+// The surface is where the summed field equals the threshold; inside, the field is above it.
+pub fn field(charges: &[Charge], p: Tuple) -> f64 {
+    charges.iter().map(|charge| charge.field(p)).sum()
+}
+
+// A set of charges placed in the scene. The charges are shared by every clone of the object, and hits
+// carry the object itself so refraction can tell when a ray leaves the surface it entered.
+pub fn new(charges: Vec<Charge>, threshold: f64) -> Object {
+    Object::new(Shape::Metaballs {charges: charges.into(), threshold})
+}
+
+fn charges(metaballs: &Object) -> (&[Charge], f64) {
+    if let Shape::Metaballs {charges, threshold} = &metaballs.shape {
+        (charges, *threshold)
+    } else {
+        (&[], 0.0)
+    }
+}
+
+pub fn local_bounds(metaballs: &Object) -> Bounds {
+    let mut bounds = Bounds::empty();
+    for charge in charges(metaballs).0 {
+        let (c, r) = (charge.centre, charge.radius);
+        bounds.add_point(point(c.x - r, c.y - r, c.z - r)).add_point(point(c.x + r, c.y + r, c.z + r));
+    }
+    bounds
+}
+
+// The spheres of influence split the ray into pieces over which the field is a fixed polynomial in t,
+// the sum of a degree-six term for each charge acting there. Solving that polynomial finds every
+// crossing, including grazing hits and thin necks that stepping along the ray could pass over.
+pub fn intersect(metaballs: &Object, ray: Ray) -> Vec<Intersection> {
+    let (charges, threshold) = charges(metaballs);
+    let spans: Vec<(f64, f64, &Charge)> = charges.iter()
+        .filter_map(|charge| charge.span(ray).map(|(t0, t1)| (t0, t1, charge)))
+        .collect();
+    let mut breaks: Vec<f64> = spans.iter().flat_map(|&(t0, t1, _)| [t0, t1]).collect();
+    breaks.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+    let mut xs = vec![];
+    for window in breaks.windows(2) {
+        let (start, end) = (window[0], window[1]);
+        let middle = (start + end) / 2.0;
+        let active: Vec<&Charge> = spans.iter()
+            .filter(|&&(t0, t1, _)| t0 <= middle && middle <= t1)
+            .map(|&(_, _, charge)| charge)
+            .collect();
+        if end - start < EPSILON || active.is_empty() {
+            continue;
+        }
+        // Measuring from the middle of the piece keeps the coefficients on the scale of the blobs.
+        let mut coefficients = [0.0; 7];
+        for charge in active {
+            for (sum, c) in coefficients.iter_mut().zip(charge.field_polynomial(ray, middle)) {
+                *sum += c;
+            }
+        }
+        coefficients[6] -= threshold;
+        for s in solve_polynomial_in(&coefficients, start - middle, end - middle) {
+            xs.push(Intersection::new(middle + s, metaballs.clone()));
+        }
+    }
+    xs
+}
+
+// The field grows towards the centres, so the outward normal in the charges' space is against its gradient.
+pub fn normal_at(metaballs: &Object, local_point: Tuple) -> Tuple {
+    let gradient = charges(metaballs).0.iter()
+        .fold(vector(0.0, 0.0, 0.0), |sum, charge| sum + charge.gradient(local_point));
+    (gradient * -1.0).normalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_hits(xs: Vec<Intersection>, expected: &[f64]) {
+        let ts: Vec<f64> = xs.iter().map(|i| i.get_t()).collect();
+        assert_eq!(ts.len(), expected.len(), "hits were {:?}", ts);
+        for (t, e) in ts.iter().zip(expected) {
+            assert!(t.approx_eq(*e), "hits were {:?}, expected {:?}", ts, expected);
+        }
+    }
+
+    #[test]
+    fn test_single_charge_is_a_sphere() {
+        // (1 - r²)³ = 0.125 at r = √0.5.
+        let m = metaballs::new(vec![Charge::new(origin(), 1.0, 1.0).unwrap()], 0.125);
+        let r = Ray::new(point(0.0, 0.0, -5.0), vector(0.0, 0.0, 1.0));
+        assert_hits(m.intersect(r), &[5.0 - FRAC_1_SQRT_2, 5.0 + FRAC_1_SQRT_2]);
+        assert_eq!(normal_at(&m, point(0.0, 0.0, -FRAC_1_SQRT_2)), vector(0.0, 0.0, -1.0));
+    }
+
+    #[test]
+    fn test_ray_misses_metaballs() {
+        let m = metaballs::new(vec![Charge::new(origin(), 1.0, 1.0).unwrap()], 0.125);
+        let grazing_influence = Ray::new(point(0.0, 0.9, -5.0), vector(0.0, 0.0, 1.0));
+        assert!(m.intersect(grazing_influence).is_empty());
+    }
+
+    #[test]
+    fn test_grazing_ray_finds_both_crossings() {
+        // (1 - r²)³ = 0.421875 at r = 0.5, and the ray dips only 1e-4 inside that.
+        let m = metaballs::new(vec![Charge::new(origin(), 1.0, 1.0).unwrap()], 0.421875);
+        let height: f64 = 0.5 - 1e-4;
+        let r = Ray::new(point(0.0, height, -5.0), vector(0.0, 0.0, 1.0));
+        let half_chord = (0.25 - height * height).sqrt();
+        assert_hits(m.intersect(r), &[5.0 - half_chord, 5.0 + half_chord]);
+    }
+
+    #[test]
+    fn test_distant_charges_stay_apart() {
+        let m = metaballs::new(vec![
+            Charge::new(point(-2.0, 0.0, 0.0), 1.0, 1.0).unwrap(),
+            Charge::new(point(2.0, 0.0, 0.0), 1.0, 1.0).unwrap(),
+        ], 0.125);
+        let r = Ray::new(point(-5.0, 0.0, 0.0), vector(1.0, 0.0, 0.0));
+        assert_hits(m.intersect(r), &[
+            3.0 - FRAC_1_SQRT_2, 3.0 + FRAC_1_SQRT_2,
+            7.0 - FRAC_1_SQRT_2, 7.0 + FRAC_1_SQRT_2,
+        ]);
+    }
+
+    #[test]
+    fn test_close_charges_merge() {
+        let charges = vec![
+            Charge::new(point(-0.6, 0.0, 0.0), 1.0, 1.0).unwrap(),
+            Charge::new(point(0.6, 0.0, 0.0), 1.0, 1.0).unwrap(),
+        ];
+        assert!(field(&charges, origin()) > 0.125);
+        let m = metaballs::new(charges, 0.125);
+        let along = Ray::new(point(-5.0, 0.0, 0.0), vector(1.0, 0.0, 0.0));
+        assert_eq!(m.intersect(along).len(), 2);
+        // Between the two centres the merged surface bulges past where either charge alone would reach.
+        let between = Ray::new(point(0.0, 5.0, 0.0), vector(0.0, -1.0, 0.0));
+        let xs = m.intersect(between);
+        assert_eq!(xs.len(), 2);
+        assert!(xs[0].get_t() < 5.0 - (0.5_f64 - 0.36).sqrt());
+    }
+
+    #[test]
+    fn test_normal_is_field_gradient() {
+        let charges = vec![
+            Charge::new(point(-0.5, 0.2, 0.0), 1.2, 1.0).unwrap(),
+            Charge::new(point(0.7, -0.1, 0.3), 0.9, 0.8).unwrap(),
+            Charge::new(point(0.0, 0.6, 0.0), 0.5, -0.3).unwrap(),
+        ];
+        let m = metaballs::new(charges.clone(), 0.2);
+        let p = point(0.1, 0.3, 0.4);
+        let h = 1e-6;
+        let numeric = vector(
+            field(&charges, p + vector(h, 0.0, 0.0)) - field(&charges, p - vector(h, 0.0, 0.0)),
+            field(&charges, p + vector(0.0, h, 0.0)) - field(&charges, p - vector(0.0, h, 0.0)),
+            field(&charges, p + vector(0.0, 0.0, h)) - field(&charges, p - vector(0.0, 0.0, h))
+        );
+        assert_eq!(normal_at(&m, p), (numeric * -1.0).normalize());
+    }
+
+    #[test]
+    fn test_charge_needs_positive_radius() {
+        assert!(Charge::new(origin(), 0.0, 1.0).is_err());
+        assert!(Charge::new(origin(), -1.0, 1.0).is_err());
+        assert!(Charge::new(origin(), f64::NAN, 1.0).is_err());
+    }
+
+    #[test]
+    fn test_tiny_charge_only_refines_its_own_span() {
+        // A faint speck further along the ray mustn't disturb the crossings of the large blob.
+        let m = metaballs::new(vec![
+            Charge::new(origin(), 1000.0, 1.0).unwrap(),
+            Charge::new(point(0.0, 0.0, 900.0), 1e-9, 1e-6).unwrap(),
+        ], 0.125);
+        let r = Ray::new(point(0.0, 0.0, -5000.0), vector(0.0, 0.0, 1.0));
+        let radius = 1000.0 * FRAC_1_SQRT_2;
+        assert_hits(m.intersect(r), &[5000.0 - radius, 5000.0 + radius]);
+    }
+
+    #[test]
+    fn test_hit_carries_field_normal() {
+        let mut m = metaballs::new(vec![Charge::new(origin(), 1.0, 1.0).unwrap()], 0.125);
+        m.set_transform(translation(0.0, 0.0, 2.0));
+        let r = Ray::new(point(0.0, 0.0, -5.0), vector(0.0, 0.0, 1.0));
+        let xs = m.intersect(r);
+        assert_hits(xs.clone(), &[7.0 - FRAC_1_SQRT_2, 7.0 + FRAC_1_SQRT_2]);
+        let normal = xs[0].get_object().normal_at(r.position(xs[0].get_t()));
+        assert_eq!(normal, vector(0.0, 0.0, -1.0));
+        // Entry and exit are the same object, so refraction knows the ray has left it.
+        assert!(xs.iter().all(|x| x.get_object() == &m));
+    }
+
+    #[test]
+    fn test_metaball_bounds() {
+        let m = metaballs::new(vec![
+            Charge::new(point(-1.0, 0.0, 0.0), 1.0, 1.0).unwrap(),
+            Charge::new(point(2.0, 1.0, 0.0), 0.5, 1.0).unwrap(),
+        ], 0.5);
+        let b = m.local_bounds();
+        assert_eq!(b.get_min(), point(-2.0, -1.0, -1.0));
+        assert_eq!(b.get_max(), point(2.5, 1.5, 1.0));
+    }
+}
@@ -1,9 +1,23 @@
 pub mod spheres;
 pub mod objects;
 pub mod planes;
+pub mod disks;
+pub mod quads;
 pub mod cubes;
 pub mod cylinders;
 pub mod cones;
+pub mod meshes;
+pub mod triangles;
+pub mod smooth_triangles;
+pub mod bezier_patches;
+pub mod tori;
+pub mod sdfs;
+pub mod metaballs;
 pub mod groups;
+pub mod csg;
+pub mod heightfields;
+pub mod instances;
 pub mod traits;
+pub mod bounds;
+pub mod bvh;
 pub mod objectholders;
@@ -5,6 +5,9 @@ use crate::shapes::traits::Transformable;
 pub enum ObjectHolder {
     Object(Object),
     Group(Group),
+    Csg(Csg),
+    Heightfield(Heightfield),
+    Instance(Instance),
 }
 
 impl std::fmt::Display for ObjectHolder {
@@ -12,6 +15,9 @@ impl std::fmt::Display for ObjectHolder {
         match self {
             ObjectHolder::Object(object) => object.shape.fmt(f),
             ObjectHolder::Group(_) => write!(f, "Group"),
+            ObjectHolder::Csg(csg) => write!(f, "CSG {}", csg.get_operation()),
+            ObjectHolder::Heightfield(_) => write!(f, "Heightfield"),
+            ObjectHolder::Instance(_) => write!(f, "Instance"),
         }
     }
 }
@@ -25,6 +31,18 @@ impl ObjectHolder {
         ObjectHolder::Group(group)
     }
 
+    pub fn from_csg(csg: Csg) -> Self {
+        ObjectHolder::Csg(csg)
+    }
+
+    pub fn from_heightfield(heightfield: Heightfield) -> Self {
+        ObjectHolder::Heightfield(heightfield)
+    }
+
+    pub fn from_instance(instance: Instance) -> Self {
+        ObjectHolder::Instance(instance)
+    }
+
     pub fn is_group(&self) -> bool {
         if let ObjectHolder::Group(_) = self {
             true
@@ -37,6 +55,29 @@ impl ObjectHolder {
         match self {
             ObjectHolder::Object(object) => object.intersect(ray),
             ObjectHolder::Group(group) => group.intersect(ray),
+            ObjectHolder::Csg(csg) => csg.intersect(ray),
+            ObjectHolder::Heightfield(heightfield) => heightfield.intersect(ray),
+            ObjectHolder::Instance(instance) => instance.intersect(ray),
+        }
+    }
+
+    pub fn get_bounds(&self) -> Bounds {
+        match self {
+            ObjectHolder::Object(object) => object.get_bounds(),
+            ObjectHolder::Group(group) => group.get_bounds(),
+            ObjectHolder::Csg(csg) => csg.get_bounds(),
+            ObjectHolder::Heightfield(heightfield) => heightfield.get_bounds(),
+            ObjectHolder::Instance(instance) => instance.get_bounds(),
+        }
+    }
+
+    pub fn build_bvh(&mut self) {
+        match self {
+            ObjectHolder::Object(_) => (),
+            ObjectHolder::Group(ref mut group) => group.build_bvh(),
+            ObjectHolder::Csg(ref mut csg) => csg.build_bvh(),
+            ObjectHolder::Heightfield(_) => (),
+            ObjectHolder::Instance(_) => (),
         }
     }
 
@@ -96,6 +137,13 @@ impl ObjectHolder {
         }
     }
 
+    pub fn get_csg(&self) -> Result<&Csg, &str> {
+        match self {
+            ObjectHolder::Csg(csg) => Ok(csg),
+            _ => Err("Called get_csg on a non-CSG element"),
+        }
+    }
+
     pub fn mut_object(&mut self) -> Result<&mut Object, &str> {
         match self {
             ObjectHolder::Object(object) => Ok(object),
@@ -109,6 +157,19 @@ impl Transformable for ObjectHolder {
         match self {
             ObjectHolder::Object(ref mut object) => object.set_transform(transform),
             ObjectHolder::Group(ref mut group) => group.set_transform(transform),
+            ObjectHolder::Csg(ref mut csg) => csg.set_transform(transform),
+            ObjectHolder::Heightfield(ref mut heightfield) => heightfield.set_transform(transform),
+            ObjectHolder::Instance(ref mut instance) => instance.set_transform(transform),
+        }
+    }
+
+    fn set_parent_transform(&mut self, parent: Matrix4) {
+        match self {
+            ObjectHolder::Object(ref mut object) => object.set_parent_transform(parent),
+            ObjectHolder::Group(ref mut group) => group.set_parent_transform(parent),
+            ObjectHolder::Csg(ref mut csg) => csg.set_parent_transform(parent),
+            ObjectHolder::Heightfield(ref mut heightfield) => heightfield.set_parent_transform(parent),
+            ObjectHolder::Instance(ref mut instance) => instance.set_parent_transform(parent),
         }
     }
 
@@ -116,6 +177,9 @@ impl Transformable for ObjectHolder {
         match self {
             ObjectHolder::Object(ref mut object) => object.set_transformation_list(index, x),
             ObjectHolder::Group(ref mut group) => group.set_transformation_list(index, x),
+            ObjectHolder::Csg(ref mut csg) => csg.set_transformation_list(index, x),
+            ObjectHolder::Heightfield(ref mut heightfield) => heightfield.set_transformation_list(index, x),
+            ObjectHolder::Instance(ref mut instance) => instance.set_transformation_list(index, x),
         }
     }
 
@@ -123,6 +187,19 @@ impl Transformable for ObjectHolder {
         match self {
             ObjectHolder::Object(object) => object.get_transform(),
             ObjectHolder::Group(group) => group.get_transform(),
+            ObjectHolder::Csg(csg) => csg.get_transform(),
+            ObjectHolder::Heightfield(heightfield) => heightfield.get_transform(),
+            ObjectHolder::Instance(instance) => instance.get_transform(),
+        }
+    }
+
+    fn get_parent_transform(&self) -> Matrix4 {
+        match self {
+            ObjectHolder::Object(object) => object.get_parent_transform(),
+            ObjectHolder::Group(group) => group.get_parent_transform(),
+            ObjectHolder::Csg(csg) => csg.get_parent_transform(),
+            ObjectHolder::Heightfield(heightfield) => heightfield.get_parent_transform(),
+            ObjectHolder::Instance(instance) => instance.get_parent_transform(),
         }
     }
 
@@ -130,6 +207,9 @@ impl Transformable for ObjectHolder {
         match self {
             ObjectHolder::Object(object) => object.get_inverse_transform(),
             ObjectHolder::Group(group) => group.get_inverse_transform(),
+            ObjectHolder::Csg(csg) => csg.get_inverse_transform(),
+            ObjectHolder::Heightfield(heightfield) => heightfield.get_inverse_transform(),
+            ObjectHolder::Instance(instance) => instance.get_inverse_transform(),
         }
     }
 
@@ -137,6 +217,9 @@ impl Transformable for ObjectHolder {
         match self {
             ObjectHolder::Object(object) => object.get_inverse_transform_transposed(),
             ObjectHolder::Group(group) => group.get_inverse_transform_transposed(),
+            ObjectHolder::Csg(csg) => csg.get_inverse_transform_transposed(),
+            ObjectHolder::Heightfield(heightfield) => heightfield.get_inverse_transform_transposed(),
+            ObjectHolder::Instance(instance) => instance.get_inverse_transform_transposed(),
         }
     }
 
@@ -144,6 +227,9 @@ impl Transformable for ObjectHolder {
         match self {
             ObjectHolder::Object(object) => object.get_transformation_list(index),
             ObjectHolder::Group(group) => group.get_transformation_list(index),
+            ObjectHolder::Csg(csg) => csg.get_transformation_list(index),
+            ObjectHolder::Heightfield(heightfield) => heightfield.get_transformation_list(index),
+            ObjectHolder::Instance(instance) => instance.get_transformation_list(index),
         }
     }
 
@@ -151,6 +237,9 @@ impl Transformable for ObjectHolder {
         match self {
             ObjectHolder::Object(object) => object.transformation_list_all(),
             ObjectHolder::Group(group) => group.transformation_list_all(),
+            ObjectHolder::Csg(csg) => csg.transformation_list_all(),
+            ObjectHolder::Heightfield(heightfield) => heightfield.transformation_list_all(),
+            ObjectHolder::Instance(instance) => instance.transformation_list_all(),
         }
     }
 
@@ -158,6 +247,9 @@ impl Transformable for ObjectHolder {
         match self {
             ObjectHolder::Object(ref mut object) => object.transformation_list_ref(),
             ObjectHolder::Group(ref mut group) => group.transformation_list_ref(),
+            ObjectHolder::Csg(ref mut csg) => csg.transformation_list_ref(),
+            ObjectHolder::Heightfield(ref mut heightfield) => heightfield.transformation_list_ref(),
+            ObjectHolder::Instance(ref mut instance) => instance.transformation_list_ref(),
         }
     }
 }
\ No newline at end of file
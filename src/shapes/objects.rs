@@ -1,32 +1,47 @@
 use crate::prelude::*;
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Object {
     material: Material,
     transform: Matrix4,
+    parent_transform: Matrix4,
     inverse_transform: Matrix4,
     inverse_transform_transposed: Matrix4,
     pub shape: Shape,
     transformations_list: [f64; 15],
 }
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum Shape {
     Sphere,
     Plane,
     Cube,
     Cylinder {min: f64, max: f64, closed: bool},
     Cone {min: f64, max: f64, closed: bool},
+    Triangle {mesh: Arc<Mesh>, points: [usize; 3], normal: usize},
+    SmoothTriangle {mesh: Arc<Mesh>, points: [usize; 3], normals: [usize; 3]},
+    Torus {major: f64, minor: f64},
+    Disk {radius: f64, inner: f64},
+    Quad {width: f64, depth: f64},
+    Sdf {root: Arc<SdfNode>},
+    Metaballs {charges: Arc<[Charge]>, threshold: f64},
 }
 
 impl std::fmt::Display for Shape {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        match *self {
+        match self {
             Shape::Sphere => write!(f, "Sphere"),
             Shape::Plane => write!(f, "Plane"),
             Shape::Cube => write!(f, "Cube"),
             Shape::Cylinder {min: _, max: _, closed: _} => write!(f, "Cylinder"),
             Shape::Cone {min: _, max: _, closed: _} => write!(f, "Cone"),
+            Shape::Triangle {..} => write!(f, "Triangle"),
+            Shape::SmoothTriangle {..} => write!(f, "Smooth Triangle"),
+            Shape::Torus {..} => write!(f, "Torus"),
+            Shape::Disk {..} => write!(f, "Disk"),
+            Shape::Quad {..} => write!(f, "Quad"),
+            Shape::Sdf {..} => write!(f, "SDF"),
+            Shape::Metaballs {..} => write!(f, "Metaballs"),
         }
     }
 }
@@ -38,6 +53,7 @@ impl Object {
         Self {
             material: Material::new(),
             transform: Matrix4::identity(),
+            parent_transform: Matrix4::identity(),
             inverse_transform: Matrix4::identity(),
             inverse_transform_transposed: Matrix4::identity(),
             shape,
@@ -53,8 +69,8 @@ impl Object {
 
     /* --------------------------- get material attributes --------------------------- */
 
-    pub fn get_material(&self) -> Material {
-        self.material
+    pub fn get_material(&self) -> &Material {
+        &self.material
     }
 
     pub fn get_color(&self) -> Color {
@@ -77,7 +93,7 @@ impl Object {
         self.material.get_shininess()
     }
 
-    pub fn get_pattern(&self) -> Pattern {
+    pub fn get_pattern(&self) -> &Pattern {
         self.material.get_pattern()
     }
 
@@ -170,39 +186,100 @@ impl Object {
     /* --------------------------- ray tracing calculations --------------------------- */
 
     pub fn normal_at(&self, pt: Tuple) -> Tuple {
+        self.normal_at_uv(pt, 0.0, 0.0)
+    }
+
+    // u and v are the barycentric coordinates of the hit, only meaningful for smooth triangles.
+    pub fn normal_at_uv(&self, pt: Tuple, u: f64, v: f64) -> Tuple {
         let local_point = self.inverse_transform * pt;
-        let local_normal = match self.shape {
+        let local_normal = match &self.shape {
             Shape::Sphere => spheres::normal_at(local_point),
             Shape::Plane => planes::normal_at(),
             Shape::Cube => cubes::normal_at(local_point),
-            Shape::Cylinder {min: _, max: _, closed: _} => cylinders::normal_at(*self, local_point),
-            Shape::Cone {min: _, max: _, closed: _} => cones::normal_at(*self, local_point),
+            Shape::Cylinder {min: _, max: _, closed: _} => cylinders::normal_at(self, local_point),
+            Shape::Cone {min: _, max: _, closed: _} => cones::normal_at(self, local_point),
+            Shape::Triangle {..} => triangles::normal_at(self),
+            Shape::SmoothTriangle {..} => smooth_triangles::normal_at(self, u, v),
+            Shape::Torus {..} => tori::normal_at(self, local_point),
+            Shape::Disk {..} => disks::normal_at(),
+            Shape::Quad {..} => quads::normal_at(),
+            Shape::Sdf {..} => sdfs::normal_at(self, local_point),
+            Shape::Metaballs {..} => metaballs::normal_at(self, local_point),
         };
         let world_normal = self.inverse_transform_transposed * local_normal;
         world_normal.vectorize().normalize()
     }
 
-    pub fn intersect(self, ray: Ray) -> Vec<Intersection> {
+    // Bounds in the object's own space, before its transform is applied.
+    pub fn local_bounds(&self) -> Bounds {
+        match self.shape {
+            Shape::Sphere => spheres::local_bounds(),
+            Shape::Plane => planes::local_bounds(),
+            Shape::Cube => cubes::local_bounds(),
+            Shape::Cylinder {min: _, max: _, closed: _} => cylinders::local_bounds(self),
+            Shape::Cone {min: _, max: _, closed: _} => cones::local_bounds(self),
+            Shape::Triangle {..} | Shape::SmoothTriangle {..} => triangles::local_bounds(self),
+            Shape::Torus {..} => tori::local_bounds(self),
+            Shape::Disk {..} => disks::local_bounds(self),
+            Shape::Quad {..} => quads::local_bounds(self),
+            Shape::Sdf {..} => sdfs::local_bounds(self),
+            Shape::Metaballs {..} => metaballs::local_bounds(self),
+        }
+    }
+
+    pub fn get_bounds(&self) -> Bounds {
+        self.local_bounds().transform(self.get_world_transform())
+    }
+
+    pub fn intersect(&self, ray: Ray) -> Vec<Intersection> {
         let local_ray = ray.transform(self.get_inverse_transform());
         self.local_intersect(local_ray)
     }
 
-    pub fn local_intersect(self, local_ray: Ray) -> Vec<Intersection> {
+    pub fn local_intersect(&self, local_ray: Ray) -> Vec<Intersection> {
         match self.shape {
             Shape::Sphere => spheres::intersect(self, local_ray),
             Shape::Plane => planes::intersect(self, local_ray),
             Shape::Cube => cubes::intersect(self, local_ray),
             Shape::Cylinder {min: _, max: _, closed: _} => cylinders::intersect(self, local_ray),
             Shape::Cone {min: _, max: _, closed: _} => cones::intersect(self, local_ray),
+            Shape::Triangle {..} => triangles::intersect(self, local_ray),
+            Shape::SmoothTriangle {..} => smooth_triangles::intersect(self, local_ray),
+            Shape::Torus {..} => tori::intersect(self, local_ray),
+            Shape::Disk {..} => disks::intersect(self, local_ray),
+            Shape::Quad {..} => quads::intersect(self, local_ray),
+            Shape::Sdf {..} => sdfs::intersect(self, local_ray),
+            Shape::Metaballs {..} => metaballs::intersect(self, local_ray),
         }
     }
 
+    fn update_world_transform(&mut self) {
+        self.inverse_transform = (self.parent_transform * self.transform).invert();
+        self.inverse_transform_transposed = self.inverse_transform.transpose();
+    }
+
+    // Places an already positioned object inside an outer transform whose inverse is known, such as an
+    // instance's, without inverting the combined matrix again.
+    pub fn apply_outer_transform(&mut self, transform: Matrix4, inverse: Matrix4) {
+        self.parent_transform = transform * self.parent_transform;
+        self.inverse_transform = self.inverse_transform * inverse;
+        self.inverse_transform_transposed = self.inverse_transform.transpose();
+    }
+
     pub fn pattern_at_object(&self, point: Tuple) -> Color {
         if self.get_pattern().get_pattern_type() == PatternType::Solid {
             return self.get_color();
         }
         let local_point = self.inverse_transform * point;
+        if self.get_pattern().get_pattern_type() == PatternType::VertexColor {
+            let (u, v) = triangles::barycentric(self, local_point);
+            return self.get_pattern().vertex_color_at(u, v);
+        }
         let pattern_space_point = self.get_pattern_inverse_transform() * local_point;
+        if self.get_pattern().get_pattern_type() == PatternType::Image {
+            let (u, v) = UvMapping::for_shape(&self.shape).map(pattern_space_point);
+            return self.get_pattern().uv_pattern_at(u, v);
+        }
         self.get_pattern().pattern_at(pattern_space_point)
     }
 }
@@ -210,8 +287,12 @@ impl Object {
 impl Transformable for Object {
     fn set_transform(&mut self, transform: Matrix4) {
         self.transform = transform;
-        self.inverse_transform = transform.invert();
-        self.inverse_transform_transposed = self.inverse_transform.transpose();
+        self.update_world_transform();
+    }
+
+    fn set_parent_transform(&mut self, parent: Matrix4) {
+        self.parent_transform = parent;
+        self.update_world_transform();
     }
 
     fn set_transformation_list(&mut self, index: usize, x: f64) {
@@ -223,6 +304,10 @@ impl Transformable for Object {
         self.transform
     }
 
+    fn get_parent_transform(&self) -> Matrix4 {
+        self.parent_transform
+    }
+
     fn get_inverse_transform(&self) -> Matrix4 {
         self.inverse_transform
     }
@@ -8,12 +8,16 @@ pub fn normal_at() -> Tuple {
     vector(0.0, 1.0, 0.0)
 }
 
-pub fn intersect(plane: Object, ray: Ray) -> Vec<Intersection> {
+pub fn local_bounds() -> Bounds {
+    Bounds::new(point(f64::NEG_INFINITY, 0.0, f64::NEG_INFINITY), point(f64::INFINITY, 0.0, f64::INFINITY))
+}
+
+pub fn intersect(plane: &Object, ray: Ray) -> Vec<Intersection> {
     if ray.get_direction().y.approx_eq(0.0) {
         return vec![]
     }
     let t = -ray.get_origin().y / ray.get_direction().y;
-    vec![Intersection::new(t, plane)]
+    vec![Intersection::new(t, plane.clone())]
 }
 
 #[cfg(test)]
@@ -24,7 +28,7 @@ mod tests {
     fn test_intersection_parallel_ray() {
         let p = new();
         let r = Ray::new(point(0.0, 10.0, 0.0), vector(0.0, 0.0, 1.0));
-        let xs = intersect(p, r);
+        let xs = intersect(&p, r);
         assert!(xs.is_empty());
     }
 
@@ -32,7 +36,7 @@ mod tests {
     fn test_intersection_coplanar_ray() {
         let p = new();
         let r = Ray::new(point(0.0, 0.0, 0.0), vector(0.0, 0.0, 1.0));
-        let xs = intersect(p, r);
+        let xs = intersect(&p, r);
         assert!(xs.is_empty());
     }
 
@@ -40,19 +44,19 @@ mod tests {
     fn test_intersection_ray_above_plane() {
         let p = new();
         let r = Ray::new(point(0.0, 1.0, 0.0), vector(0.0, -1.0, 0.0));
-        let xs = intersect(p, r);
+        let xs = intersect(&p, r);
         assert_eq!(xs.len(), 1);
         assert_eq!(xs[0].get_t(), 1.0);
-        assert_eq!(xs[0].get_object(), p);
+        assert_eq!(xs[0].get_object(), &p);
     }
 
     #[test]
     fn test_intersection_ray_below_plane() {
         let p = new();
         let r = Ray::new(point(0.0, -1.0, 0.0), vector(0.0, 1.0, 0.0));
-        let xs = intersect(p, r);
+        let xs = intersect(&p, r);
         assert_eq!(xs.len(), 1);
         assert_eq!(xs[0].get_t(), 1.0);
-        assert_eq!(xs[0].get_object(), p);
+        assert_eq!(xs[0].get_object(), &p);
     }
 }
\ No newline at end of file
@@ -0,0 +1,72 @@
+use crate::prelude::*;
+
+// A rectangle in the xz plane facing +y, centred on the origin and spanning width along x and
+// depth along z.
+pub fn new(width: f64, depth: f64) -> Object {
+    Object::new(Shape::Quad {width, depth})
+}
+
+fn half_extents(quad: &Object) -> (f64, f64) {
+    if let Shape::Quad {width, depth} = quad.shape {
+        (width / 2.0, depth / 2.0)
+    } else {
+        (0.5, 0.5)
+    }
+}
+
+pub fn normal_at() -> Tuple {
+    vector(0.0, 1.0, 0.0)
+}
+
+pub fn local_bounds(quad: &Object) -> Bounds {
+    let (half_width, half_depth) = half_extents(quad);
+    Bounds::new(point(-half_width, 0.0, -half_depth), point(half_width, 0.0, half_depth))
+}
+
+pub fn intersect(quad: &Object, ray: Ray) -> Vec<Intersection> {
+    if ray.get_direction().y.approx_eq(0.0) {
+        return vec![]
+    }
+    let t = -ray.get_origin().y / ray.get_direction().y;
+    let x = ray.get_origin().x + t * ray.get_direction().x;
+    let z = ray.get_origin().z + t * ray.get_direction().z;
+
+    let (half_width, half_depth) = half_extents(quad);
+    if x.abs() > half_width || z.abs() > half_depth {
+        return vec![]
+    }
+    vec![Intersection::new(t, quad.clone())]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ray_hits_quad() {
+        let q = new(2.0, 4.0);
+        let r = Ray::new(point(0.9, -2.0, 1.9), vector(0.0, 1.0, 0.0));
+        let xs = intersect(&q, r);
+        assert_eq!(xs.len(), 1);
+        assert_eq!(xs[0].get_t(), 2.0);
+        assert_eq!(xs[0].get_object(), &q);
+    }
+
+    #[test]
+    fn test_ray_misses_quad() {
+        let q = new(2.0, 4.0);
+        let past_x = Ray::new(point(1.1, 1.0, 0.0), vector(0.0, -1.0, 0.0));
+        let past_z = Ray::new(point(0.0, 1.0, -2.1), vector(0.0, -1.0, 0.0));
+        let parallel = Ray::new(point(-5.0, 0.0, 0.0), vector(1.0, 0.0, 0.0));
+        assert!(intersect(&q, past_x).is_empty());
+        assert!(intersect(&q, past_z).is_empty());
+        assert!(intersect(&q, parallel).is_empty());
+    }
+
+    #[test]
+    fn test_quad_bounds() {
+        let b = local_bounds(&new(2.0, 4.0));
+        assert_eq!(b.get_min(), point(-1.0, 0.0, -2.0));
+        assert_eq!(b.get_max(), point(1.0, 0.0, 2.0));
+    }
+}
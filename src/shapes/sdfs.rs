@@ -0,0 +1,274 @@
+use crate::prelude::*;
+
+const MAX_STEPS: usize = 512;
+const HIT_DISTANCE: f64 = 1e-6;
+const GRADIENT_STEP: f64 = 1e-5;
+
+// A tree of signed distance functions. Distances are negative inside the surface. The smooth
+// combinations blend their children over a distance of roughly k; a k of zero gives a sharp edge.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SdfNode {
+    Sphere {radius: f64},
+    Cuboid {half_extents: Tuple},
+    RoundedCuboid {half_extents: Tuple, radius: f64},
+    Capsule {a: Tuple, b: Tuple, radius: f64},
+    Torus {major: f64, minor: f64},
+    Translate {offset: Tuple, node: Box<SdfNode>},
+    SmoothUnion {a: Box<SdfNode>, b: Box<SdfNode>, k: f64},
+    SmoothSubtraction {a: Box<SdfNode>, b: Box<SdfNode>, k: f64},
+    SmoothIntersection {a: Box<SdfNode>, b: Box<SdfNode>, k: f64},
+}
+
+impl SdfNode {
+    pub fn translate(self, x: f64, y: f64, z: f64) -> SdfNode {
+        SdfNode::Translate {offset: vector(x, y, z), node: Box::new(self)}
+    }
+
+    pub fn smooth_union(self, other: SdfNode, k: f64) -> SdfNode {
+        SdfNode::SmoothUnion {a: Box::new(self), b: Box::new(other), k}
+    }
+
+    // Carves other out of self.
+    pub fn smooth_subtraction(self, other: SdfNode, k: f64) -> SdfNode {
+        SdfNode::SmoothSubtraction {a: Box::new(self), b: Box::new(other), k}
+    }
+
+    pub fn smooth_intersection(self, other: SdfNode, k: f64) -> SdfNode {
+        SdfNode::SmoothIntersection {a: Box::new(self), b: Box::new(other), k}
+    }
+
+    pub fn distance(&self, p: Tuple) -> f64 {
+        match self {
+            SdfNode::Sphere {radius} => length(p.x, p.y, p.z) - radius,
+            SdfNode::Cuboid {half_extents} => cuboid_distance(p, *half_extents),
+            SdfNode::RoundedCuboid {half_extents, radius} => {
+                let inner = vector(half_extents.x - radius, half_extents.y - radius, half_extents.z - radius);
+                cuboid_distance(p, inner) - radius
+            },
+            SdfNode::Capsule {a, b, radius} => {
+                let (pa, ba) = (p - *a, *b - *a);
+                let h = ((pa * ba) / (ba * ba)).clamp(0.0, 1.0);
+                (pa - ba * h).magnitude() - radius
+            },
+            SdfNode::Torus {major, minor} => length(p.x.hypot(p.z) - major, p.y, 0.0) - minor,
+            SdfNode::Translate {offset, node} => node.distance(p - *offset),
+            SdfNode::SmoothUnion {a, b, k} => smooth_min(a.distance(p), b.distance(p), *k),
+            SdfNode::SmoothSubtraction {a, b, k} => -smooth_min(-a.distance(p), b.distance(p), *k),
+            SdfNode::SmoothIntersection {a, b, k} => -smooth_min(-a.distance(p), -b.distance(p), *k),
+        }
+    }
+
+    // Conservative box around the surface. A smooth union can bulge past its children by k / 4.
+    pub fn bounds(&self) -> Bounds {
+        match self {
+            SdfNode::Sphere {radius} => symmetric_bounds(*radius, *radius, *radius),
+            SdfNode::Cuboid {half_extents} | SdfNode::RoundedCuboid {half_extents, ..} =>
+                symmetric_bounds(half_extents.x, half_extents.y, half_extents.z),
+            SdfNode::Capsule {a, b, radius} => {
+                let mut bounds = Bounds::empty();
+                bounds.add_point(*a).add_point(*b);
+                padded(bounds, *radius)
+            },
+            SdfNode::Torus {major, minor} => symmetric_bounds(major + minor, *minor, major + minor),
+            SdfNode::Translate {offset, node} => node.bounds().transform(translation(offset.x, offset.y, offset.z)),
+            SdfNode::SmoothUnion {a, b, k} => {
+                let mut bounds = a.bounds();
+                bounds.merge(b.bounds());
+                padded(bounds, k / 4.0)
+            },
+            SdfNode::SmoothSubtraction {a, ..} | SdfNode::SmoothIntersection {a, ..} => a.bounds(),
+        }
+    }
+}
+
+fn length(x: f64, y: f64, z: f64) -> f64 {
+    (x * x + y * y + z * z).sqrt()
+}
+
+fn cuboid_distance(p: Tuple, half_extents: Tuple) -> f64 {
+    let q = [p.x.abs() - half_extents.x, p.y.abs() - half_extents.y, p.z.abs() - half_extents.z];
+    let outside = length(q[0].max(0.0), q[1].max(0.0), q[2].max(0.0));
+    let inside = q[0].max(q[1]).max(q[2]).min(0.0);
+    outside + inside
+}
+
+// Polynomial smooth minimum; subtraction and intersection are built from it by negating distances.
+fn smooth_min(a: f64, b: f64, k: f64) -> f64 {
+    if k <= 0.0 {
+        return a.min(b);
+    }
+    let h = (0.5 + 0.5 * (b - a) / k).clamp(0.0, 1.0);
+    b * (1.0 - h) + a * h - k * h * (1.0 - h)
+}
+
+fn symmetric_bounds(x: f64, y: f64, z: f64) -> Bounds {
+    Bounds::new(point(-x, -y, -z), point(x, y, z))
+}
+
+fn padded(bounds: Bounds, pad: f64) -> Bounds {
+    let (min, max) = (bounds.get_min(), bounds.get_max());
+    Bounds::new(point(min.x - pad, min.y - pad, min.z - pad), point(max.x + pad, max.y + pad, max.z + pad))
+}
+
+// A distance tree placed in the scene. The tree is shared by every clone of the object, and hits
+// carry the object itself so refraction can tell when a ray leaves the surface it entered.
+pub fn new(root: SdfNode) -> Object {
+    Object::new(Shape::Sdf {root: Arc::new(root)})
+}
+
+static UNIT_SPHERE: SdfNode = SdfNode::Sphere {radius: 1.0};
+
+fn root(sdf: &Object) -> &SdfNode {
+    if let Shape::Sdf {root} = &sdf.shape {
+        root
+    } else {
+        &UNIT_SPHERE
+    }
+}
+
+pub fn local_bounds(sdf: &Object) -> Bounds {
+    root(sdf).bounds()
+}
+
+// Sphere tracing across the bounding box. Every surface crossing is reported, so refraction and
+// shadows see both the entry and exit points; inside the surface the ray steps by |distance| too.
+pub fn intersect(sdf: &Object, ray: Ray) -> Vec<Intersection> {
+    let root = root(sdf);
+    let mut xs = vec![];
+    let (mut t, t_exit) = match root.bounds().clip(ray) {
+        Some(span) => span,
+        None => return xs,
+    };
+    let speed = ray.get_direction().magnitude();
+
+    for _ in 0..MAX_STEPS {
+        if t > t_exit {
+            break;
+        }
+        let distance = root.distance(ray.position(t)).abs();
+        if distance < HIT_DISTANCE {
+            xs.push(Intersection::new(t, sdf.clone()));
+            // Step across the surface so the next crossing isn't found at the same point.
+            t += 10.0 * HIT_DISTANCE / speed;
+        } else {
+            t += distance / speed;
+        }
+    }
+    xs
+}
+
+// The normal in the tree's own space, from a central difference of the distances.
+pub fn normal_at(sdf: &Object, local_point: Tuple) -> Tuple {
+    let root = root(sdf);
+    let gradient = |offset: Tuple| root.distance(local_point + offset) - root.distance(local_point - offset);
+    vector(
+        gradient(vector(GRADIENT_STEP, 0.0, 0.0)),
+        gradient(vector(0.0, GRADIENT_STEP, 0.0)),
+        gradient(vector(0.0, 0.0, GRADIENT_STEP))
+    ).normalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_hits(xs: Vec<Intersection>, expected: &[f64]) {
+        let ts: Vec<f64> = xs.iter().map(|i| i.get_t()).collect();
+        assert_eq!(ts.len(), expected.len(), "hits were {:?}", ts);
+        for (t, e) in ts.iter().zip(expected) {
+            assert!((t - e).abs() < 1e-4, "hits were {:?}, expected {:?}", ts, expected);
+        }
+    }
+
+    #[test]
+    fn test_primitive_distances() {
+        let p = point(2.0, 0.0, 0.0);
+        assert!(SdfNode::Sphere {radius: 1.0}.distance(p).approx_eq(1.0));
+        assert!(SdfNode::Cuboid {half_extents: vector(1.0, 1.0, 1.0)}.distance(point(2.0, 2.0, 1.0)).approx_eq(SQRT_2));
+        assert!(SdfNode::Cuboid {half_extents: vector(1.0, 2.0, 3.0)}.distance(origin()).approx_eq(-1.0));
+        let rounded = SdfNode::RoundedCuboid {half_extents: vector(1.0, 1.0, 1.0), radius: 0.5};
+        assert!(rounded.distance(p).approx_eq(1.0));
+        assert!(rounded.distance(point(2.0, 2.0, 0.0)).approx_eq(1.5 * SQRT_2 - 0.5));
+        let capsule = SdfNode::Capsule {a: point(0.0, -1.0, 0.0), b: point(0.0, 1.0, 0.0), radius: 0.5};
+        assert!(capsule.distance(p).approx_eq(1.5));
+        assert!(capsule.distance(point(0.0, 3.0, 0.0)).approx_eq(1.5));
+        let torus = SdfNode::Torus {major: 1.0, minor: 0.25};
+        assert!(torus.distance(point(0.0, 0.0, 1.25)).approx_eq(0.0));
+        assert!(torus.distance(origin()).approx_eq(0.75));
+    }
+
+    #[test]
+    fn test_sharp_combinations() {
+        let a = SdfNode::Sphere {radius: 1.0};
+        let b = SdfNode::Sphere {radius: 1.0}.translate(1.5, 0.0, 0.0);
+        let p = point(-2.0, 0.0, 0.0);
+        assert!(a.clone().smooth_union(b.clone(), 0.0).distance(p).approx_eq(1.0));
+        assert!(a.clone().smooth_intersection(b.clone(), 0.0).distance(p).approx_eq(2.5));
+        assert!(a.smooth_subtraction(b, 0.0).distance(point(0.5, 0.0, 0.0)).approx_eq(0.0));
+    }
+
+    #[test]
+    fn test_smooth_union_blends() {
+        let a = SdfNode::Sphere {radius: 1.0}.translate(-1.2, 0.0, 0.0);
+        let b = SdfNode::Sphere {radius: 1.0}.translate(1.2, 0.0, 0.0);
+        let midpoint = point(0.0, 0.0, 0.0);
+        let sharp = a.clone().smooth_union(b.clone(), 0.0).distance(midpoint);
+        let smooth = a.smooth_union(b, 1.0).distance(midpoint);
+        assert!(sharp.approx_eq(0.2));
+        assert!(smooth < 0.0);
+    }
+
+    #[test]
+    fn test_ray_marches_to_sphere() {
+        let s = sdfs::new(SdfNode::Sphere {radius: 1.0});
+        let r = Ray::new(point(0.0, 0.0, -5.0), vector(0.0, 0.0, 1.0));
+        assert_hits(s.intersect(r), &[4.0, 6.0]);
+        let miss = Ray::new(point(0.0, 2.0, -5.0), vector(0.0, 0.0, 1.0));
+        assert_hits(s.intersect(miss), &[]);
+    }
+
+    #[test]
+    fn test_ray_marches_with_unnormalized_direction() {
+        let s = sdfs::new(SdfNode::Sphere {radius: 1.0});
+        let r = Ray::new(point(0.0, 0.0, -5.0), vector(0.0, 0.0, 2.0));
+        assert_hits(s.intersect(r), &[2.0, 3.0]);
+    }
+
+    #[test]
+    fn test_ray_through_subtracted_hole() {
+        let ring = SdfNode::Cuboid {half_extents: vector(2.0, 1.0, 2.0)}
+            .smooth_subtraction(SdfNode::Capsule {a: point(0.0, -3.0, 0.0), b: point(0.0, 3.0, 0.0), radius: 1.0}, 0.0);
+        let s = sdfs::new(ring);
+        let r = Ray::new(point(-5.0, 0.0, 0.0), vector(1.0, 0.0, 0.0));
+        assert_hits(s.intersect(r), &[3.0, 4.0, 6.0, 7.0]);
+    }
+
+    #[test]
+    fn test_normal_from_gradient() {
+        let s = sdfs::new(SdfNode::Cuboid {half_extents: vector(1.0, 1.0, 1.0)});
+        assert_eq!(normal_at(&s, point(1.0, 0.5, -0.8)), vector(1.0, 0.0, 0.0));
+        let t = sdfs::new(SdfNode::Sphere {radius: 1.0}.translate(0.0, 1.0, 0.0));
+        let n = normal_at(&t, point(0.0, 1.0 + FRAC_1_SQRT_2, FRAC_1_SQRT_2));
+        assert_eq!(n, vector(0.0, FRAC_1_SQRT_2, FRAC_1_SQRT_2));
+    }
+
+    #[test]
+    fn test_hit_carries_gradient_normal() {
+        let mut s = sdfs::new(SdfNode::Sphere {radius: 1.0});
+        s.set_transform(translation(0.0, 0.0, 2.0));
+        let r = Ray::new(point(0.0, 0.0, -5.0), vector(0.0, 0.0, 1.0));
+        let xs = s.intersect(r);
+        assert_hits(xs.clone(), &[6.0, 8.0]);
+        let normal = xs[0].get_object().normal_at(r.position(xs[0].get_t()));
+        assert_eq!(normal, vector(0.0, 0.0, -1.0));
+        assert_eq!(s.get_bounds().get_max(), point(1.0, 1.0, 3.0));
+    }
+
+    #[test]
+    fn test_sdf_bounds() {
+        let blob = SdfNode::Sphere {radius: 1.0}.smooth_union(SdfNode::Sphere {radius: 1.0}.translate(2.0, 0.0, 0.0), 0.4);
+        let b = sdfs::new(blob).local_bounds();
+        assert_eq!(b.get_min(), point(-1.1, -1.1, -1.1));
+        assert_eq!(b.get_max(), point(3.1, 1.1, 1.1));
+    }
+}
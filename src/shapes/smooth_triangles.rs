@@ -0,0 +1,65 @@
+use crate::prelude::*;
+
+pub fn new(p1: Tuple, p2: Tuple, p3: Tuple, n1: Tuple, n2: Tuple, n3: Tuple) -> Object {
+    let mut mesh = Mesh::new();
+    let points = [mesh.add_point(p1), mesh.add_point(p2), mesh.add_point(p3)];
+    let normals = [mesh.add_normal(n1), mesh.add_normal(n2), mesh.add_normal(n3)];
+    from_mesh(&Arc::new(mesh), points, normals)
+}
+
+// A smooth triangle whose corners and their normals are stored in a mesh shared with the rest of a model.
+pub fn from_mesh(mesh: &Arc<Mesh>, points: [usize; 3], normals: [usize; 3]) -> Object {
+    Object::new(Shape::SmoothTriangle {mesh: mesh.clone(), points, normals})
+}
+
+// Interpolates the vertex normals using the barycentric coordinates recorded on the intersection.
+pub fn normal_at(triangle: &Object, u: f64, v: f64) -> Tuple {
+    if let Shape::SmoothTriangle {mesh, normals, ..} = &triangle.shape {
+        let [n1, n2, n3] = normals.map(|index| mesh.get_normal(index));
+        n2 * u + n3 * v + n1 * (1.0 - u - v)
+    } else {
+        vector(0.0, 0.0, 0.0)
+    }
+}
+
+pub fn intersect(triangle: &Object, ray: Ray) -> Vec<Intersection> {
+    let [p1, p2, p3] = triangles::corners(triangle);
+    match triangles::intersect_barycentric(p1, p2 - p1, p3 - p1, ray) {
+        Some((t, u, v)) => vec![Intersection::new_with_uv(t, triangle.clone(), u, v)],
+        None => vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn default_smooth_triangle() -> Object {
+        smooth_triangles::new(
+            point(0.0, 1.0, 0.0),
+            point(-1.0, 0.0, 0.0),
+            point(1.0, 0.0, 0.0),
+            vector(0.0, 1.0, 0.0),
+            vector(-1.0, 0.0, 0.0),
+            vector(1.0, 0.0, 0.0)
+        )
+    }
+
+    #[test]
+    fn test_intersection_stores_uv() {
+        let tri = default_smooth_triangle();
+        let r = Ray::new(point(-0.2, 0.3, -2.0), vector(0.0, 0.0, 1.0));
+        let xs = intersect(&tri, r);
+        assert_eq!(xs.len(), 1);
+        assert!(xs[0].get_u().approx_eq(0.45));
+        assert!(xs[0].get_v().approx_eq(0.25));
+    }
+
+    #[test]
+    fn test_normal_interpolated() {
+        let tri = default_smooth_triangle();
+        let n = tri.normal_at_uv(origin(), 0.45, 0.25);
+        assert_eq!(n, vector(-0.5547, 0.83205, 0.0));
+    }
+
+}
@@ -17,11 +17,15 @@ pub fn glass_sphere() -> Object {
     sphere.set_specular(0.9);
     sphere
 }
+pub fn local_bounds() -> Bounds {
+    Bounds::new(point(-1.0, -1.0, -1.0), point(1.0, 1.0, 1.0))
+}
+
 pub fn normal_at(pt: Tuple) -> Tuple {
     pt - point(0.0, 0.0, 0.0)
 }
 
-pub fn intersect(sphere: Object, ray: Ray) -> Vec<Intersection> {
+pub fn intersect(sphere: &Object, ray: Ray) -> Vec<Intersection> {
     let vec_from_sphere_to_ray = ray.get_origin() - origin();
     let a = ray.get_direction() * ray.get_direction();
     let b = 2.0 * (ray.get_direction() * vec_from_sphere_to_ray);
@@ -35,9 +39,9 @@ pub fn intersect(sphere: Object, ray: Ray) -> Vec<Intersection> {
     let t1 = (-b - discriminant.sqrt()) / (2.0 * a);
     let t2 = (-b + discriminant.sqrt()) / (2.0 * a);
     if t1 < t2 {
-        vec![Intersection::new(t1, sphere), Intersection::new(t2, sphere)]
+        vec![Intersection::new(t1, sphere.clone()), Intersection::new(t2, sphere.clone())]
     } else {
-        vec![Intersection::new(t2, sphere), Intersection::new(t1, sphere)]
+        vec![Intersection::new(t2, sphere.clone()), Intersection::new(t1, sphere.clone())]
     }
 }
 
@@ -45,9 +49,9 @@ pub fn hit(intersections: &Vec<Intersection>) -> Option<Intersection> {
     if intersections.is_empty() {
         None
     } else if intersections[0].get_t() >= 0.0 {
-        Some(intersections[0])
+        Some(intersections[0].clone())
     } else if intersections[1].get_t() >= 0.0 {
-        Some(intersections[1])
+        Some(intersections[1].clone())
     } else {
         None
     }
@@ -62,12 +66,12 @@ mod tests {
         let ray = Ray::new(point(0.0, 0.0, -5.0),
                            vector(0.0, 0.0, 1.0));
         let sphere = spheres::new();
-        let xs = spheres::intersect(sphere, ray);
+        let xs = spheres::intersect(&sphere, ray);
         assert_eq!(xs.len(), 2);
-        assert_eq!(xs[0].get_object(), Intersection::new(4.0, sphere).get_object());
-        assert_eq!(xs[0].get_t(), Intersection::new(4.0, sphere).get_t());
-        assert_eq!(xs[1].get_object(), Intersection::new(6.0, sphere).get_object());
-        assert_eq!(xs[1].get_t(), Intersection::new(6.0, sphere).get_t());
+        assert_eq!(xs[0].get_object(), Intersection::new(4.0, sphere.clone()).get_object());
+        assert_eq!(xs[0].get_t(), Intersection::new(4.0, sphere.clone()).get_t());
+        assert_eq!(xs[1].get_object(), Intersection::new(6.0, sphere.clone()).get_object());
+        assert_eq!(xs[1].get_t(), Intersection::new(6.0, sphere.clone()).get_t());
     }
 
     #[test]
@@ -75,12 +79,12 @@ mod tests {
         let ray = Ray::new(point(0.0, 1.0, -5.0),
                            vector(0.0, 0.0, 1.0));
         let sphere = spheres::new();
-        let xs = spheres::intersect(sphere, ray);
+        let xs = spheres::intersect(&sphere, ray);
         assert_eq!(xs.len(), 2);
-        assert_eq!(xs[0].get_object(), Intersection::new(5.0, sphere).get_object());
-        assert_eq!(xs[1].get_object(), Intersection::new(5.0, sphere).get_object());
-        assert_eq!(xs[0].get_t(), Intersection::new(5.0, sphere).get_t());
-        assert_eq!(xs[1].get_t(), Intersection::new(5.0, sphere).get_t());
+        assert_eq!(xs[0].get_object(), Intersection::new(5.0, sphere.clone()).get_object());
+        assert_eq!(xs[1].get_object(), Intersection::new(5.0, sphere.clone()).get_object());
+        assert_eq!(xs[0].get_t(), Intersection::new(5.0, sphere.clone()).get_t());
+        assert_eq!(xs[1].get_t(), Intersection::new(5.0, sphere.clone()).get_t());
     }
 
     #[test]
@@ -88,7 +92,7 @@ mod tests {
         let ray = Ray::new(point(0.0, 2.0, -5.0),
                            vector(0.0, 0.0, 1.0));
         let sphere = spheres::new();
-        let xs = spheres::intersect(sphere, ray);
+        let xs = spheres::intersect(&sphere, ray);
         assert!(xs.is_empty());
     }
 
@@ -97,12 +101,12 @@ mod tests {
         let ray = Ray::new(point(0.0, 0.0, 0.0),
                            vector(0.0, 0.0, 1.0));
         let sphere = spheres::new();
-        let xs = spheres::intersect(sphere, ray);
+        let xs = spheres::intersect(&sphere, ray);
         assert_eq!(xs.len(), 2);
-        assert_eq!(xs[0].get_object(), Intersection::new(-1.0, sphere).get_object());
-        assert_eq!(xs[1].get_object(), Intersection::new(1.0, sphere).get_object());
-        assert_eq!(xs[0].get_t(), Intersection::new(-1.0, sphere).get_t());
-        assert_eq!(xs[1].get_t(), Intersection::new(1.0, sphere).get_t());
+        assert_eq!(xs[0].get_object(), Intersection::new(-1.0, sphere.clone()).get_object());
+        assert_eq!(xs[1].get_object(), Intersection::new(1.0, sphere.clone()).get_object());
+        assert_eq!(xs[0].get_t(), Intersection::new(-1.0, sphere.clone()).get_t());
+        assert_eq!(xs[1].get_t(), Intersection::new(1.0, sphere.clone()).get_t());
     }
 
     #[test]
@@ -114,12 +118,12 @@ mod tests {
         sphere.set_transform(scaling(2.0, 2.0, 2.0));
 
         let local_ray = ray.transform(sphere.get_inverse_transform());
-        let xs = spheres::intersect(sphere, local_ray);
+        let xs = spheres::intersect(&sphere, local_ray);
         assert_eq!(xs.len(), 2);
-        assert_eq!(xs[0].get_object(), Intersection::new(3.0, sphere).get_object());
-        assert_eq!(xs[0].get_t(), Intersection::new(3.0, sphere).get_t());
-        assert_eq!(xs[1].get_object(), Intersection::new(7.0, sphere).get_object());
-        assert_eq!(xs[1].get_t(), Intersection::new(7.0, sphere).get_t());
+        assert_eq!(xs[0].get_object(), Intersection::new(3.0, sphere.clone()).get_object());
+        assert_eq!(xs[0].get_t(), Intersection::new(3.0, sphere.clone()).get_t());
+        assert_eq!(xs[1].get_object(), Intersection::new(7.0, sphere.clone()).get_object());
+        assert_eq!(xs[1].get_t(), Intersection::new(7.0, sphere.clone()).get_t());
     }
 
     #[test]
@@ -131,7 +135,7 @@ mod tests {
         sphere.set_transform(translation(5.0, 0.0, 0.0));
 
         let local_ray = ray.transform(sphere.get_inverse_transform());
-        assert!(spheres::intersect(sphere, local_ray).is_empty());
+        assert!(spheres::intersect(&sphere, local_ray).is_empty());
     }
 
     #[test]
@@ -140,7 +144,7 @@ mod tests {
                            vector(0.0, 0.0, 1.0));
         let sphere = spheres::new();
         let local_ray = ray.transform(sphere.get_inverse_transform());
-        let xs = spheres::intersect(sphere, local_ray);
+        let xs = spheres::intersect(&sphere, local_ray);
         let hit = spheres::hit(&xs).unwrap();
         assert_eq!(hit.get_t(), xs[0].get_t());
         assert_eq!(hit.get_object(), xs[0].get_object());
@@ -153,7 +157,7 @@ mod tests {
                            vector(0.0, 0.0, 1.0));
         let sphere = spheres::new();
         let local_ray = ray.transform(sphere.get_inverse_transform());
-        let xs = spheres::intersect(sphere, local_ray);
+        let xs = spheres::intersect(&sphere, local_ray);
         spheres::hit(&xs).unwrap();
     }
 
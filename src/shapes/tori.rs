@@ -0,0 +1,126 @@
+use crate::prelude::*;
+
+// A torus centred on the origin and lying in the xz plane. The major radius runs from the origin to
+// the centre of the tube and the minor radius is the radius of the tube itself.
+pub fn new(major: f64, minor: f64) -> Object {
+    Object::new(Shape::Torus {major, minor})
+}
+
+fn radii(torus: &Object) -> (f64, f64) {
+    if let Shape::Torus {major, minor} = torus.shape {
+        (major, minor)
+    } else {
+        (1.0, 0.25)
+    }
+}
+
+pub fn local_bounds(torus: &Object) -> Bounds {
+    let (major, minor) = radii(torus);
+    let outer = major + minor;
+    Bounds::new(point(-outer, -minor, -outer), point(outer, minor, outer))
+}
+
+// Substituting the ray into (x² + y² + z² + R² - r²)² = 4R²(x² + z²) gives a quartic in t.
+// The ray starts from where it enters the bounding box, keeping the coefficients on the scale of the
+// torus however far away the ray comes from, and that offset is added back to each root.
+pub fn intersect(torus: &Object, ray: Ray) -> Vec<Intersection> {
+    let (major, minor) = radii(torus);
+    let offset = match local_bounds(torus).clip(ray) {
+        Some((t_enter, _)) => t_enter,
+        None => return vec![],
+    };
+    let origin = ray.position(offset);
+    let direction = ray.get_direction();
+
+    let dir_sq = direction * direction;
+    let e = origin.x.powi(2) + origin.y.powi(2) + origin.z.powi(2) - major.powi(2) - minor.powi(2);
+    let f = origin.x * direction.x + origin.y * direction.y + origin.z * direction.z;
+    let four_major_sq = 4.0 * major.powi(2);
+
+    let c4 = dir_sq.powi(2);
+    let c3 = 4.0 * dir_sq * f;
+    let c2 = 2.0 * dir_sq * e + 4.0 * f.powi(2) + four_major_sq * direction.y.powi(2);
+    let c1 = 4.0 * f * e + 2.0 * four_major_sq * origin.y * direction.y;
+    let c0 = e.powi(2) - four_major_sq * (minor.powi(2) - origin.y.powi(2));
+
+    solve_quartic(c4, c3, c2, c1, c0)
+        .into_iter()
+        .map(|t| Intersection::new(t + offset, torus.clone()))
+        .collect()
+}
+
+pub fn normal_at(torus: &Object, point: Tuple) -> Tuple {
+    let (major, minor) = radii(torus);
+    let dist_sq = point.x.powi(2) + point.y.powi(2) + point.z.powi(2);
+    let radial = dist_sq - major.powi(2) - minor.powi(2);
+    vector(
+        point.x * radial,
+        point.y * (radial + 2.0 * major.powi(2)),
+        point.z * radial
+    ).normalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ray_through_the_hole() {
+        let t = new(1.0, 0.25);
+        let r = Ray::new(point(-5.0, 0.0, 0.0), vector(1.0, 0.0, 0.0));
+        let xs = intersect(&t, r);
+        let ts: Vec<f64> = xs.iter().map(|i| i.get_t()).collect();
+        assert_eq!(ts.len(), 4);
+        for (actual, expected) in ts.iter().zip([3.75, 4.25, 5.75, 6.25]) {
+            assert!(actual.approx_eq(expected));
+        }
+    }
+
+    #[test]
+    fn test_ray_through_the_tube() {
+        let t = new(1.0, 0.25);
+        let r = Ray::new(point(0.0, 5.0, 1.0), vector(0.0, -1.0, 0.0));
+        let xs = intersect(&t, r);
+        assert_eq!(xs.len(), 2);
+        assert!(xs[0].get_t().approx_eq(4.75));
+        assert!(xs[1].get_t().approx_eq(5.25));
+    }
+
+    #[test]
+    fn test_distant_ray() {
+        let t = new(1.0, 0.25);
+        let r = Ray::new(point(-1000.0, 0.1, 0.0), vector(1.0, 0.0, 0.0));
+        let xs = intersect(&t, r);
+        assert_eq!(xs.len(), 4);
+        let tube = (0.25f64.powi(2) - 0.1f64.powi(2)).sqrt();
+        for (i, x) in [-1.0 - tube, -1.0 + tube, 1.0 - tube, 1.0 + tube].into_iter().enumerate() {
+            assert!(xs[i].get_t().approx_eq(1000.0 + x), "{} != {}", xs[i].get_t(), 1000.0 + x);
+        }
+    }
+
+    #[test]
+    fn test_ray_misses_torus() {
+        let t = new(1.0, 0.25);
+        let down_the_hole = Ray::new(point(0.0, 5.0, 0.0), vector(0.0, -1.0, 0.0));
+        let beside = Ray::new(point(-5.0, 0.5, 0.0), vector(1.0, 0.0, 0.0));
+        assert!(intersect(&t, down_the_hole).is_empty());
+        assert!(intersect(&t, beside).is_empty());
+    }
+
+    #[test]
+    fn test_normal_on_torus() {
+        let t = new(1.0, 0.25);
+        assert_eq!(normal_at(&t, point(1.25, 0.0, 0.0)), vector(1.0, 0.0, 0.0));
+        assert_eq!(normal_at(&t, point(0.75, 0.0, 0.0)), vector(-1.0, 0.0, 0.0));
+        assert_eq!(normal_at(&t, point(0.0, 0.25, 1.0)), vector(0.0, 1.0, 0.0));
+        assert_eq!(normal_at(&t, point(0.0, 0.0, -1.25)), vector(0.0, 0.0, -1.0));
+    }
+
+    #[test]
+    fn test_torus_bounds() {
+        let t = new(2.0, 0.5);
+        let b = local_bounds(&t);
+        assert_eq!(b.get_min(), point(-2.5, -0.5, -2.5));
+        assert_eq!(b.get_max(), point(2.5, 0.5, 2.5));
+    }
+}
@@ -1,8 +1,13 @@
 use crate::prelude::*;
 
+// get_transform is relative to the parent group, while the inverse transforms map from world space
+// straight into the shape's own space through the whole chain of parents.
 pub trait Transformable {
     fn set_transform(&mut self, transform: Matrix4);
 
+    // Called by the containing group whenever its own world transform changes.
+    fn set_parent_transform(&mut self, parent: Matrix4);
+
     fn set_transformation_list(&mut self, index: usize, x: f64);
 
     fn update_transform(&mut self) {
@@ -32,17 +37,6 @@ pub trait Transformable {
         self.set_transform(translation * scaling * rotation_x * rotation_y * rotation_z * shear);
     }
 
-    fn combine_transforms(&mut self, list: [f64; 15]) {
-        for i in 0..15 {
-            if i <= 2 || i >= 6 {
-                self.transformation_list_ref()[i] += list[i]
-            } else {
-                self.transformation_list_ref()[i] *= list[i];
-            }
-        }
-        self.update_transform();
-    }
-
     fn translate_x(&mut self, x: f64) {
         self.set_transformation_list(0, x);
     }
@@ -101,6 +95,12 @@ pub trait Transformable {
 
     fn get_transform(&self) -> Matrix4;
 
+    fn get_parent_transform(&self) -> Matrix4;
+
+    fn get_world_transform(&self) -> Matrix4 {
+        self.get_parent_transform() * self.get_transform()
+    }
+
     fn get_inverse_transform(&self) -> Matrix4;
 
     fn get_inverse_transform_transposed(&self) -> Matrix4;
@@ -0,0 +1,141 @@
+use crate::prelude::*;
+
+pub fn new(p1: Tuple, p2: Tuple, p3: Tuple) -> Object {
+    let normal = (p3 - p1).xprod(p2 - p1).normalize();
+    new_with_normal(p1, p2, p3, normal)
+}
+
+// For mesh formats that store a facet normal rather than relying on the winding order.
+pub fn new_with_normal(p1: Tuple, p2: Tuple, p3: Tuple, normal: Tuple) -> Object {
+    let mut mesh = Mesh::new();
+    let points = [mesh.add_point(p1), mesh.add_point(p2), mesh.add_point(p3)];
+    let normal = mesh.add_normal(normal);
+    from_mesh(&Arc::new(mesh), points, normal)
+}
+
+// A triangle whose corners and normal are stored in a mesh shared with the rest of a model.
+pub fn from_mesh(mesh: &Arc<Mesh>, points: [usize; 3], normal: usize) -> Object {
+    Object::new(Shape::Triangle {mesh: mesh.clone(), points, normal})
+}
+
+// The corners of a triangle or smooth triangle.
+pub fn corners(triangle: &Object) -> [Tuple; 3] {
+    match &triangle.shape {
+        Shape::Triangle {mesh, points, ..} | Shape::SmoothTriangle {mesh, points, ..} => {
+            points.map(|index| mesh.get_point(index))
+        },
+        _ => [origin(); 3],
+    }
+}
+
+pub fn local_bounds(triangle: &Object) -> Bounds {
+    let mut bounds = Bounds::empty();
+    for corner in corners(triangle) {
+        bounds.add_point(corner);
+    }
+    bounds
+}
+
+pub fn normal_at(triangle: &Object) -> Tuple {
+    if let Shape::Triangle {mesh, normal, ..} = &triangle.shape {
+        mesh.get_normal(*normal)
+    } else {
+        vector(0.0, 0.0, 0.0)
+    }
+}
+
+pub fn intersect(triangle: &Object, ray: Ray) -> Vec<Intersection> {
+    let [p1, p2, p3] = corners(triangle);
+    match intersect_barycentric(p1, p2 - p1, p3 - p1, ray) {
+        Some((t, _, _)) => vec![Intersection::new(t, triangle.clone())],
+        None => vec![],
+    }
+}
+
+// Barycentric weights of p2 and p3 for a point in the plane of a triangle or smooth triangle.
+pub fn barycentric(triangle: &Object, pt: Tuple) -> (f64, f64) {
+    let [p1, p2, p3] = corners(triangle);
+    let (e1, e2) = (p2 - p1, p3 - p1);
+    let w = pt - p1;
+    let (d00, d01, d11) = (e1 * e1, e1 * e2, e2 * e2);
+    let (d20, d21) = (w * e1, w * e2);
+    let denom = d00 * d11 - d01 * d01;
+    ((d11 * d20 - d01 * d21) / denom, (d00 * d21 - d01 * d20) / denom)
+}
+
+// Möller–Trumbore: returns t along with the barycentric u and v of the hit, shared with smooth triangles.
+pub fn intersect_barycentric(p1: Tuple, e1: Tuple, e2: Tuple, ray: Ray) -> Option<(f64, f64, f64)> {
+    let dir_cross_e2 = ray.get_direction().xprod(e2);
+    let det = e1 * dir_cross_e2;
+    if det.abs() < EPSILON {
+        return None;
+    }
+
+    let f = 1.0 / det;
+    let p1_to_origin = ray.get_origin() - p1;
+    let u = f * (p1_to_origin * dir_cross_e2);
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+
+    let origin_cross_e1 = p1_to_origin.xprod(e1);
+    let v = f * (ray.get_direction() * origin_cross_e1);
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+
+    Some((f * (e2 * origin_cross_e1), u, v))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn default_triangle() -> Object {
+        triangles::new(point(0.0, 1.0, 0.0), point(-1.0, 0.0, 0.0), point(1.0, 0.0, 0.0))
+    }
+
+    #[test]
+    fn test_construct_triangle() {
+        let t = default_triangle();
+        assert_eq!(corners(&t), [point(0.0, 1.0, 0.0), point(-1.0, 0.0, 0.0), point(1.0, 0.0, 0.0)]);
+        assert_eq!(normal_at(&t), vector(0.0, 0.0, -1.0));
+    }
+
+    #[test]
+    fn test_triangle_normal() {
+        let t = default_triangle();
+        assert_eq!(t.normal_at(point(0.0, 0.5, 0.0)), vector(0.0, 0.0, -1.0));
+        assert_eq!(t.normal_at(point(-0.5, 0.75, 0.0)), vector(0.0, 0.0, -1.0));
+        assert_eq!(t.normal_at(point(0.5, 0.25, 0.0)), vector(0.0, 0.0, -1.0));
+    }
+
+    #[test]
+    fn test_parallel_ray_misses() {
+        let t = default_triangle();
+        let r = Ray::new(point(0.0, -1.0, -2.0), vector(0.0, 1.0, 0.0));
+        assert!(intersect(&t, r).is_empty());
+    }
+
+    #[test]
+    fn test_ray_misses_edges() {
+        let t = default_triangle();
+        let r = Ray::new(point(1.0, 1.0, -2.0), vector(0.0, 0.0, 1.0));
+        assert!(intersect(&t, r).is_empty());
+
+        let r = Ray::new(point(-1.0, 1.0, -2.0), vector(0.0, 0.0, 1.0));
+        assert!(intersect(&t, r).is_empty());
+
+        let r = Ray::new(point(0.0, -1.0, -2.0), vector(0.0, 0.0, 1.0));
+        assert!(intersect(&t, r).is_empty());
+    }
+
+    #[test]
+    fn test_ray_strikes_triangle() {
+        let t = default_triangle();
+        let r = Ray::new(point(0.0, 0.5, -2.0), vector(0.0, 0.0, 1.0));
+        let xs = intersect(&t, r);
+        assert_eq!(xs.len(), 1);
+        assert_eq!(xs[0].get_t(), 2.0);
+    }
+}
@@ -55,7 +55,7 @@ impl ops::Add<Color> for Color {
 
 impl ops::AddAssign<Color> for Color {
     fn add_assign(&mut self, other: Color) {
-        *self = *self + color(self.red + other.red, self.green + other.green, self.blue + other.blue)
+        *self = *self + other
     }
 }
 
@@ -104,6 +104,13 @@ mod tests {
         assert_eq!(sum, color(1.6, 0.7, 1.0));
     }
 
+    #[test]
+    fn test_add_assign_colors() {
+        let mut c1 = color(0.9, 0.6, 0.75);
+        c1 += color(0.7, 0.1, 0.25);
+        assert_eq!(c1, color(1.6, 0.7, 1.0));
+    }
+
     #[test]
     fn test_sub_colors() {
         let c1 = color(0.9, 0.6, 0.75);
@@ -1,6 +1,6 @@
 use crate::prelude::*;
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Material {
     color: Color,
     ambient: f64,
@@ -75,8 +75,8 @@ impl Material {
         self
     }
 
-    pub fn get_pattern(&self) -> Pattern {
-        self.pattern
+    pub fn get_pattern(&self) -> &Pattern {
+        &self.pattern
     }
 
     pub fn set_pattern(&mut self, pattern: Pattern) -> &mut Self {
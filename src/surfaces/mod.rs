@@ -1,3 +1,5 @@
 pub mod materials;
 pub mod colors;
-pub mod patterns;
\ No newline at end of file
+pub mod patterns;
+pub mod textures;
+pub mod uv_maps;
@@ -1,12 +1,15 @@
 use crate::prelude::*;
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Clone, Debug)]
 pub struct Pattern {
     pattern_type: PatternType,
     transform: Matrix4,
     inverse_transform: Matrix4,
     color1: Color,
     color2: Color,
+    color3: Color,
+    texture: Option<Arc<Texture>>,
+    wrap: WrapMode,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq)]
@@ -16,6 +19,8 @@ pub enum PatternType {
     Gradient,
     Ring,
     Checker3d,
+    VertexColor,
+    Image,
     Test,
 }
 
@@ -27,11 +32,31 @@ impl std::fmt::Display for Pattern {
             PatternType::Gradient => write!(f, "Gradient"),
             PatternType::Ring => write!(f, "Ring"),
             PatternType::Checker3d => write!(f, "Checkers"),
+            PatternType::VertexColor => write!(f, "Vertex Color"),
+            PatternType::Image => write!(f, "Image Texture"),
             _ => write!(f, "Test"),
         }
     }
 }
 
+// Textures compare by identity, so checking whether two objects are the same never walks their pixels.
+impl PartialEq for Pattern {
+    fn eq(&self, other: &Self) -> bool {
+        let same_texture = match (&self.texture, &other.texture) {
+            (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        };
+        self.pattern_type == other.pattern_type
+            && self.transform == other.transform
+            && self.color1 == other.color1
+            && self.color2 == other.color2
+            && self.color3 == other.color3
+            && same_texture
+            && self.wrap == other.wrap
+    }
+}
+
 impl Pattern {
     pub fn new(pattern_type: PatternType, color1: Color, color2: Color) -> Self {
         Self {
@@ -40,6 +65,9 @@ impl Pattern {
             inverse_transform: Matrix4::identity(),
             color1,
             color2,
+            color3: black(),
+            texture: None,
+            wrap: WrapMode::Repeat,
         }
     }
 
@@ -50,6 +78,9 @@ impl Pattern {
             inverse_transform: self.inverse_transform,
             color1: self.color1,
             color2: self.color2,
+            color3: self.color3,
+            texture: self.texture.clone(),
+            wrap: self.wrap,
         }
     }
 
@@ -60,6 +91,9 @@ impl Pattern {
             inverse_transform: self.inverse_transform,
             color1: color,
             color2: self.color2,
+            color3: self.color3,
+            texture: self.texture.clone(),
+            wrap: self.wrap,
         }
     }
 
@@ -70,6 +104,9 @@ impl Pattern {
             inverse_transform: self.inverse_transform,
             color1: self.color1,
             color2: color,
+            color3: self.color3,
+            texture: self.texture.clone(),
+            wrap: self.wrap,
         }
     }
 
@@ -97,11 +134,40 @@ impl Pattern {
             PatternType::Gradient => gradient_at(self.color1, self.color2, point),
             PatternType::Ring => ring_at(self.color1, self.color2, point),
             PatternType::Checker3d => checker_3d_at(self.color1, self.color2, point),
+            PatternType::VertexColor => self.color1,
+            PatternType::Image => self.color1,
             PatternType::Solid => black(),
             PatternType::Test => color(point.x, point.y, point.z),
         }
     }
 
+    // Blends the colours of a triangle's three vertices by the barycentric weights of its second and third.
+    pub fn vertex_color_at(&self, u: f64, v: f64) -> Color {
+        self.color1 * (1.0 - u - v) + self.color2 * u + self.color3 * v
+    }
+
+    // Texture lookup for shapes that have unwrapped the hit point to (u, v). Without an image the first
+    // colour is shown instead.
+    pub fn uv_pattern_at(&self, u: f64, v: f64) -> Color {
+        match &self.texture {
+            Some(texture) => texture.sample(u, v, self.wrap),
+            None => self.color1,
+        }
+    }
+
+    pub fn get_texture(&self) -> Option<&Arc<Texture>> {
+        self.texture.as_ref()
+    }
+
+    pub fn get_wrap_mode(&self) -> WrapMode {
+        self.wrap
+    }
+
+    pub fn set_wrap_mode(&mut self, wrap: WrapMode) -> &mut Self {
+        self.wrap = wrap;
+        self
+    }
+
     pub fn colors (&self) -> [Color; 2] {
         [self.color1, self.color2]
     }
@@ -137,6 +203,20 @@ pub fn checker_3d(a: Color, b: Color) -> Pattern {
     Pattern::new(PatternType::Checker3d, a, b)
 }
 
+pub fn vertex_color(a: Color, b: Color, c: Color) -> Pattern {
+    let mut pattern = Pattern::new(PatternType::VertexColor, a, b);
+    pattern.color3 = c;
+    pattern
+}
+
+// The texture is shared, so one image can be loaded once and used by any number of patterns.
+pub fn image_texture(texture: Arc<Texture>, wrap: WrapMode) -> Pattern {
+    let mut pattern = Pattern::new(PatternType::Image, white(), black());
+    pattern.texture = Some(texture);
+    pattern.wrap = wrap;
+    pattern
+}
+
 pub fn test_pattern() -> Pattern {
     Pattern::new(PatternType::Test, black(), black())
 }
@@ -268,4 +348,47 @@ mod tests {
         let c = object.pattern_at_object(point(1.5, 0.0, 0.0));
         assert_eq!(c, white());
     }
+
+    #[test]
+    fn test_vertex_color_on_triangle() {
+        let mut t = triangles::new(point(0.0, 1.0, 0.0), point(-1.0, 0.0, 0.0), point(1.0, 0.0, 0.0));
+        t.set_pattern(vertex_color(color(1.0, 0.0, 0.0), color(0.0, 1.0, 0.0), color(0.0, 0.0, 1.0)));
+        assert_eq!(t.pattern_at_object(point(0.0, 1.0, 0.0)), color(1.0, 0.0, 0.0));
+        assert_eq!(t.pattern_at_object(point(-1.0, 0.0, 0.0)), color(0.0, 1.0, 0.0));
+        assert_eq!(t.pattern_at_object(point(0.0, 0.0, 0.0)), color(0.0, 0.5, 0.5));
+        assert_eq!(t.pattern_at_object(point(-0.2, 0.3, 0.0)), color(0.3, 0.45, 0.25));
+    }
+
+    fn two_pixel_texture() -> Arc<Texture> {
+        Arc::new(Texture::new(2, 1, vec![color(1.0, 0.0, 0.0), color(0.0, 0.0, 1.0)]).unwrap())
+    }
+
+    #[test]
+    fn test_image_texture_on_sphere() {
+        let mut s = spheres::new();
+        s.set_transform(scaling(2.0, 2.0, 2.0));
+        s.set_pattern(image_texture(two_pixel_texture(), WrapMode::Clamp));
+        // u = 0.25 and 0.75 fall on the centres of the left and right pixels.
+        assert_eq!(s.pattern_at_object(point(2.0, 0.0, 0.0)), color(1.0, 0.0, 0.0));
+        assert_eq!(s.pattern_at_object(point(-2.0, 0.0, 0.0)), color(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn test_image_texture_wrapping_on_plane() {
+        let texture = two_pixel_texture();
+        let mut p = planes::new();
+        p.set_pattern(image_texture(texture.clone(), WrapMode::Clamp));
+        assert_eq!(p.pattern_at_object(point(0.25, 0.0, 0.5)), color(1.0, 0.0, 0.0));
+        assert_eq!(p.pattern_at_object(point(7.25, 0.0, 0.5)), color(0.0, 0.0, 1.0));
+        p.set_pattern(image_texture(texture, WrapMode::Repeat));
+        assert_eq!(p.pattern_at_object(point(7.25, 0.0, 0.5)), color(1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn test_patterns_share_textures_by_identity() {
+        let texture = two_pixel_texture();
+        let a = image_texture(texture.clone(), WrapMode::Repeat);
+        assert_eq!(a, image_texture(texture, WrapMode::Repeat));
+        assert_ne!(a, image_texture(two_pixel_texture(), WrapMode::Repeat));
+    }
 }
@@ -0,0 +1,158 @@
+use crate::prelude::*;
+use std::path::Path;
+
+// What a texture shows outside the [0, 1] square: another copy of itself, or its edge pixels.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum WrapMode {
+    Repeat,
+    Clamp,
+}
+
+impl std::fmt::Display for WrapMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match *self {
+            WrapMode::Repeat => write!(f, "Repeat"),
+            WrapMode::Clamp => write!(f, "Clamp"),
+        }
+    }
+}
+
+// An image stored row by row from the top. Channels are the stored bytes scaled to [0, 1] with no sRGB
+// decoding, matching the canvas, which writes colours back out the same way.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Texture {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>,
+}
+
+impl Texture {
+    pub fn new(width: usize, height: usize, pixels: Vec<Color>) -> Result<Self, &'static str> {
+        if width == 0 || height == 0 {
+            return Err("A texture needs at least one pixel");
+        }
+        if pixels.len() != width * height {
+            return Err("Number of pixels does not match the texture size");
+        }
+        Ok(Self {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    pub fn from_image(image: &image::DynamicImage) -> Self {
+        let rgb = image.to_rgb8();
+        let pixels = rgb.pixels()
+            .map(|p| color(p[0] as f64 / 255.0, p[1] as f64 / 255.0, p[2] as f64 / 255.0))
+            .collect();
+        Self {
+            width: rgb.width() as usize,
+            height: rgb.height() as usize,
+            pixels,
+        }
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> image::ImageResult<Self> {
+        Ok(Texture::from_image(&image::open(path)?))
+    }
+
+    pub fn get_width(&self) -> usize {
+        self.width
+    }
+
+    pub fn get_height(&self) -> usize {
+        self.height
+    }
+
+    pub fn pixel_at(&self, x: usize, y: usize) -> Color {
+        self.pixels[y * self.width + x]
+    }
+
+    // Bilinear filtering between the four pixel centres around (u, v). Row 0 is the top of the image,
+    // where v is 1.
+    pub fn sample(&self, u: f64, v: f64, wrap: WrapMode) -> Color {
+        let x = u * self.width as f64 - 0.5;
+        let y = (1.0 - v) * self.height as f64 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as isize, y0 as isize);
+
+        let texel = |x: isize, y: isize| {
+            self.pixel_at(wrap_index(x, self.width, wrap), wrap_index(y, self.height, wrap))
+        };
+        // Coordinates far outside the texture saturate when cast, so stepping to the next pixel mustn't overflow.
+        let (x1, y1) = (x0.saturating_add(1), y0.saturating_add(1));
+        let top = texel(x0, y0) * (1.0 - fx) + texel(x1, y0) * fx;
+        let bottom = texel(x0, y1) * (1.0 - fx) + texel(x1, y1) * fx;
+        top * (1.0 - fy) + bottom * fy
+    }
+}
+
+fn wrap_index(index: isize, size: usize, wrap: WrapMode) -> usize {
+    match wrap {
+        WrapMode::Repeat => index.rem_euclid(size as isize) as usize,
+        WrapMode::Clamp => index.clamp(0, size as isize - 1) as usize,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Black and white on the top row, red and green on the bottom.
+    fn quad() -> Texture {
+        Texture::new(2, 2, vec![black(), white(), color(1.0, 0.0, 0.0), color(0.0, 1.0, 0.0)]).unwrap()
+    }
+
+    #[test]
+    fn test_texture_size_must_match() {
+        assert!(Texture::new(0, 1, vec![]).is_err());
+        assert!(Texture::new(2, 2, vec![black(); 3]).is_err());
+    }
+
+    #[test]
+    fn test_sampling_pixel_centres() {
+        let t = quad();
+        assert_eq!(t.sample(0.25, 0.75, WrapMode::Clamp), black());
+        assert_eq!(t.sample(0.75, 0.75, WrapMode::Clamp), white());
+        assert_eq!(t.sample(0.25, 0.25, WrapMode::Clamp), color(1.0, 0.0, 0.0));
+        assert_eq!(t.sample(0.75, 0.25, WrapMode::Repeat), color(0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn test_bilinear_filtering() {
+        let t = quad();
+        assert_eq!(t.sample(0.5, 0.5, WrapMode::Clamp), color(0.5, 0.5, 0.25));
+        assert_eq!(t.sample(0.5, 0.75, WrapMode::Clamp), color(0.5, 0.5, 0.5));
+    }
+
+    #[test]
+    fn test_wrap_modes() {
+        let t = quad();
+        // Halfway between the left and right columns across the seam.
+        assert_eq!(t.sample(0.0, 0.75, WrapMode::Repeat), color(0.5, 0.5, 0.5));
+        assert_eq!(t.sample(0.0, 0.75, WrapMode::Clamp), black());
+        assert_eq!(t.sample(1.75, 0.75, WrapMode::Repeat), white());
+        assert_eq!(t.sample(1.75, 0.75, WrapMode::Clamp), white());
+        assert_eq!(t.sample(-3.0, -2.0, WrapMode::Clamp), color(1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn test_sampling_far_outside_texture() {
+        let t = quad();
+        assert_eq!(t.sample(1e300, 0.75, WrapMode::Clamp), white());
+        assert_eq!(t.sample(-1e300, 1e300, WrapMode::Clamp), black());
+        t.sample(1e300, -1e300, WrapMode::Repeat);
+    }
+
+    #[test]
+    fn test_texture_from_image() {
+        let image = image::RgbImage::from_raw(2, 1, vec![255, 0, 0, 0, 51, 255]).unwrap();
+        let t = Texture::from_image(&image::DynamicImage::ImageRgb8(image));
+        assert_eq!(t.get_width(), 2);
+        assert_eq!(t.get_height(), 1);
+        assert_eq!(t.pixel_at(0, 0), color(1.0, 0.0, 0.0));
+        assert_eq!(t.pixel_at(1, 0), color(0.0, 0.2, 1.0));
+    }
+}
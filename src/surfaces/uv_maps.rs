@@ -0,0 +1,142 @@
+use crate::prelude::*;
+
+// How a point in a shape's own space is unwrapped onto the (u, v) square of a texture. v runs up
+// the texture. Planar and cylindrical maps don't wrap their output, so a texture's wrap mode decides
+// whether the image repeats or stays a single decal.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum UvMapping {
+    Spherical,
+    Planar,
+    Cylindrical,
+    Cube,
+}
+
+impl UvMapping {
+    pub fn for_shape(shape: &Shape) -> UvMapping {
+        match shape {
+            Shape::Sphere | Shape::Sdf {..} | Shape::Metaballs {..} => UvMapping::Spherical,
+            Shape::Cylinder {..} | Shape::Cone {..} | Shape::Torus {..} => UvMapping::Cylindrical,
+            Shape::Cube => UvMapping::Cube,
+            _ => UvMapping::Planar,
+        }
+    }
+
+    pub fn map(&self, point: Tuple) -> (f64, f64) {
+        match self {
+            UvMapping::Spherical => spherical_map(point),
+            UvMapping::Planar => planar_map(point),
+            UvMapping::Cylindrical => cylindrical_map(point),
+            UvMapping::Cube => cube_map(point),
+        }
+    }
+}
+
+// Longitude from the -z axis going anticlockwise seen from above, and latitude from the south pole.
+pub fn spherical_map(point: Tuple) -> (f64, f64) {
+    let theta = point.x.atan2(point.z);
+    let radius = (point.x.powi(2) + point.y.powi(2) + point.z.powi(2)).sqrt();
+    let phi = (point.y / radius).acos();
+    let raw_u = theta / (2.0 * PI);
+    (1.0 - (raw_u + 0.5), 1.0 - phi / PI)
+}
+
+pub fn planar_map(point: Tuple) -> (f64, f64) {
+    (point.x, point.z)
+}
+
+pub fn cylindrical_map(point: Tuple) -> (f64, f64) {
+    let theta = point.x.atan2(point.z);
+    let raw_u = theta / (2.0 * PI);
+    (1.0 - (raw_u + 0.5), point.y)
+}
+
+// Each face of the cube gets the whole texture, seen from outside the cube with +y up on the sides.
+pub fn cube_map(point: Tuple) -> (f64, f64) {
+    let coord = |value: f64| value.rem_euclid(2.0) / 2.0;
+    let largest = point.x.abs().max(point.y.abs()).max(point.z.abs());
+    if largest == point.x {
+        (coord(1.0 - point.z), coord(point.y + 1.0))
+    } else if largest == -point.x {
+        (coord(point.z + 1.0), coord(point.y + 1.0))
+    } else if largest == point.y {
+        (coord(point.x + 1.0), coord(1.0 - point.z))
+    } else if largest == -point.y {
+        (coord(point.x + 1.0), coord(point.z + 1.0))
+    } else if largest == point.z {
+        (coord(point.x + 1.0), coord(point.y + 1.0))
+    } else {
+        (coord(1.0 - point.x), coord(point.y + 1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_uv(actual: (f64, f64), expected: (f64, f64)) {
+        assert!(actual.0.approx_eq(expected.0) && actual.1.approx_eq(expected.1),
+                "uv was {:?}, expected {:?}", actual, expected);
+    }
+
+    #[test]
+    fn test_spherical_mapping() {
+        let cases = [
+            (point(0.0, 0.0, -1.0), (0.0, 0.5)),
+            (point(1.0, 0.0, 0.0), (0.25, 0.5)),
+            (point(0.0, 0.0, 1.0), (0.5, 0.5)),
+            (point(-1.0, 0.0, 0.0), (0.75, 0.5)),
+            (point(0.0, 1.0, 0.0), (0.5, 1.0)),
+            (point(0.0, -1.0, 0.0), (0.5, 0.0)),
+            (point(FRAC_1_SQRT_2, FRAC_1_SQRT_2, 0.0), (0.25, 0.75)),
+        ];
+        for (p, uv) in cases {
+            assert_uv(spherical_map(p), uv);
+        }
+    }
+
+    #[test]
+    fn test_planar_mapping() {
+        assert_uv(planar_map(point(0.25, 0.0, 0.5)), (0.25, 0.5));
+        assert_uv(planar_map(point(-1.25, 0.7, 3.5)), (-1.25, 3.5));
+    }
+
+    #[test]
+    fn test_cylindrical_mapping() {
+        let cases = [
+            (point(0.0, 0.0, -1.0), (0.0, 0.0)),
+            (point(0.0, 0.5, -1.0), (0.0, 0.5)),
+            (point(FRAC_1_SQRT_2, 0.5, -FRAC_1_SQRT_2), (0.125, 0.5)),
+            (point(1.0, 0.5, 0.0), (0.25, 0.5)),
+            (point(FRAC_1_SQRT_2, 0.5, FRAC_1_SQRT_2), (0.375, 0.5)),
+            (point(0.0, -0.25, 1.0), (0.5, -0.25)),
+            (point(-1.0, 1.25, 0.0), (0.75, 1.25)),
+        ];
+        for (p, uv) in cases {
+            assert_uv(cylindrical_map(p), uv);
+        }
+    }
+
+    #[test]
+    fn test_cube_mapping() {
+        // The same spot towards the upper left of every face, as seen from outside.
+        let cases = [
+            point(-0.5, 0.5, 1.0),
+            point(0.5, 0.5, -1.0),
+            point(-1.0, 0.5, -0.5),
+            point(1.0, 0.5, 0.5),
+            point(-0.5, 1.0, -0.5),
+            point(-0.5, -1.0, 0.5),
+        ];
+        for p in cases {
+            assert_uv(cube_map(p), (0.25, 0.75));
+        }
+    }
+
+    #[test]
+    fn test_mapping_for_shape() {
+        assert_eq!(UvMapping::for_shape(&Shape::Sphere), UvMapping::Spherical);
+        assert_eq!(UvMapping::for_shape(&Shape::Plane), UvMapping::Planar);
+        assert_eq!(UvMapping::for_shape(&Shape::Cone {min: 0.0, max: 1.0, closed: true}), UvMapping::Cylindrical);
+        assert_eq!(UvMapping::for_shape(&Shape::Cube), UvMapping::Cube);
+    }
+}